@@ -0,0 +1,38 @@
+use profiler::{save_to_file, scope, submit_frame};
+use profiler::log::ProfilerLogger;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::time::Duration;
+
+// stands in for the logger the application already uses
+struct StderrLogger;
+
+impl Log for StderrLogger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		metadata.level() <= log::Level::Info
+	}
+
+	fn log(&self, record: &Record) {
+		eprintln!("[{}] {}", record.level(), record.args());
+	}
+
+	fn flush(&self) {}
+}
+
+fn main() {
+	ProfilerLogger::new(StderrLogger).init(LevelFilter::Debug).unwrap();
+
+	for i in 0..8 {
+		{
+			scope!(format!("frame_{i}"));
+			log::debug!("simulating frame {i}");
+			let spike = i % 3 == 2;
+			if spike {
+				log::warn!("cache miss, reloading assets");
+			}
+			std::thread::sleep(Duration::from_millis(if spike { 30 } else { 10 }));
+		}
+		submit_frame!();
+	}
+
+	save_to_file!("saved.profiling");
+}
@@ -1,4 +1,4 @@
-use profiler::{save_to_file, scope, submit_frame};
+use profiler::{define_global_frames, save_to_file, scope, submit_frame};
 
 fn worker_thread() {
 	for _ in 0..10 {
@@ -11,14 +11,25 @@ fn worker_thread() {
 }
 
 fn main() {
+	// the main loop defines the frames that all worker threads are grouped by
+	define_global_frames!();
+
 	let mut worker_threads = Vec::new();
 	for i in 0..10 {
 		worker_threads.push(std::thread::Builder::new().name(format!("worker thread {i}")).spawn(worker_thread).unwrap());
 	}
 
+	for _ in 0..5 {
+		{
+			scope!("main loop");
+			std::thread::sleep(std::time::Duration::from_millis(200));
+		}
+		submit_frame!();
+	}
+
 	for thread in worker_threads {
 		thread.join().unwrap();
 	}
 
 	save_to_file!("saved.profiling");
-}
\ No newline at end of file
+}
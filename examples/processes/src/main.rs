@@ -0,0 +1,47 @@
+use std::process::Command;
+use profiler::{save_to_file, scope, submit_frame};
+use profiler::process::{merge_child_sessions, save_to_parent_session, CommandExt};
+use profiler_attributes::profile;
+
+#[profile]
+fn compile_shader(index: u64) {
+	std::thread::sleep(std::time::Duration::from_millis(5 + index));
+}
+
+// runs in the child processes, which are this executable started with the argument "shader_compiler"
+fn shader_compiler() {
+	for i in 0..4 {
+		compile_shader(i);
+		submit_frame!();
+	}
+	save_to_parent_session().expect("Failed to save to the parent session");
+}
+
+fn main() {
+	if std::env::args().nth(1).as_deref() == Some("shader_compiler") {
+		shader_compiler();
+		return;
+	}
+
+	let dir = std::env::temp_dir().join(format!("profiler_processes_{}", std::process::id()));
+	{
+		scope!("spawn_shader_compilers");
+		let children: Vec<_> = (0..2)
+			.map(|_| Command::new(std::env::current_exe().unwrap())
+				.arg("shader_compiler")
+				.share_session(&dir)
+				.expect("Failed to share the session")
+				.spawn()
+				.unwrap())
+			.collect();
+		for mut child in children {
+			scope!("wait_for_shader_compiler");
+			child.wait().unwrap();
+		}
+	}
+	submit_frame!();
+
+	merge_child_sessions(&dir).expect("Failed to merge the child processes");
+	std::fs::remove_dir_all(&dir).unwrap();
+	save_to_file!("saved.profiling");
+}
@@ -0,0 +1,47 @@
+use profiler::{save_to_file, scope, submit_frame};
+use profiler::rayon::in_parent;
+use profiler_attributes::profile;
+use rayon::prelude::*;
+
+#[profile]
+fn simulate_particle(seed: u64) -> u64 {
+	let mut value = seed;
+	for _ in 0..200_000 {
+		value = std::hint::black_box(value.wrapping_mul(6364136223846793005).wrapping_add(1));
+	}
+	value
+}
+
+#[profile]
+fn update_particles() -> u64 {
+	(0..64u64).into_par_iter()
+		.map(in_parent(simulate_particle))
+		.reduce(|| 0, u64::wrapping_add)
+}
+
+#[profile]
+fn build_meshes() {
+	profiler::rayon::join(
+		|| { scope!("terrain"); simulate_particle(1); },
+		|| { scope!("water"); simulate_particle(2); },
+	);
+}
+
+fn main() {
+	profiler::rayon::instrument_thread_pool(rayon::ThreadPoolBuilder::new())
+		.num_threads(4)
+		.build_global()
+		.unwrap();
+
+	for i in 0..4 {
+		{
+			scope!(format!("frame_{i}"));
+			update_particles();
+			build_meshes();
+		}
+		profiler::rayon::submit_worker_frames();
+		submit_frame!();
+	}
+
+	save_to_file!("saved.profiling");
+}
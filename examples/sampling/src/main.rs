@@ -0,0 +1,39 @@
+use profiler::{save_to_file, scope, submit_frame};
+use std::time::{Duration, Instant};
+
+// not instrumented, only visible through samples
+#[inline(never)]
+fn spin(duration: Duration) -> u64 {
+	let start = Instant::now();
+	let mut counter = 0u64;
+	while start.elapsed() < duration {
+		counter = std::hint::black_box(counter.wrapping_add(1));
+	}
+	counter
+}
+
+#[inline(never)]
+fn physics() -> u64 {
+	spin(Duration::from_millis(20))
+}
+
+#[inline(never)]
+fn ai() -> u64 {
+	spin(Duration::from_millis(10))
+}
+
+fn main() {
+	profiler::sampling::register_current_thread();
+	profiler::sampling::start_sampling(Duration::from_millis(1));
+
+	for i in 0..10 {
+		{
+			scope!(format!("frame_{i}"));
+			std::hint::black_box(physics() + ai());
+		}
+		submit_frame!();
+	}
+
+	profiler::sampling::stop_sampling();
+	save_to_file!("saved.profiling");
+}
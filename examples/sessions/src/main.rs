@@ -0,0 +1,35 @@
+use profiler::{scope, submit_frame, start_session, end_session, save_sessions_to_file};
+use profiler_attributes::profile;
+
+#[profile]
+fn load_assets() {
+	std::thread::sleep(std::time::Duration::from_millis(20));
+}
+
+#[profile]
+fn update(level: usize) {
+	std::thread::sleep(std::time::Duration::from_millis(5 * level as u64));
+}
+
+fn main() {
+	let mut sessions = Vec::new();
+
+	start_session("startup").expect("No session is active yet");
+	load_assets();
+	submit_frame!();
+	sessions.push(end_session());
+
+	for level in 1..=2 {
+		start_session(format!("level {level}")).expect("The previous level ended its session");
+		for i in 0..10 {
+			{
+				scope!(format!("frame_{i}"));
+				update(level);
+			}
+			submit_frame!();
+		}
+		sessions.push(end_session());
+	}
+
+	save_sessions_to_file(&sessions, "saved.profiling").expect("Failed to write to file saved.profiling");
+}
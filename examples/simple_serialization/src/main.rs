@@ -5,14 +5,14 @@ use profiler_attributes::profile;
 fn work_1() {
 	println!("work_1");
 
-	std::thread::sleep(std::time::Duration::from_millis(500));
+	profiler::sleep(std::time::Duration::from_millis(500));
 }
 
 #[profile]
 fn work_2() {
 	println!("work_2");
 
-	std::thread::sleep(std::time::Duration::from_millis(500));
+	profiler::sleep(std::time::Duration::from_millis(500));
 }
 
 fn main() {
@@ -0,0 +1,35 @@
+use std::time::{Duration, Instant};
+use profiler::{submit_frame, scope};
+use profiler::server::{start_server, DEFAULT_ADDRESS};
+use profiler::control::{start_control_server, DEFAULT_CONTROL_ADDRESS};
+use profiler_attributes::profile;
+
+#[profile]
+fn simulate(frame: u64) {
+	std::thread::sleep(Duration::from_millis(4 + frame % 5));
+}
+
+#[profile]
+fn render() {
+	std::thread::sleep(Duration::from_millis(6));
+}
+
+// run `profiler_viewer --connect` while this is running to watch the frames arrive,
+// and e.g. `profiler_viewer --control capture 100` and `profiler_viewer --control save capture.profiling` to save some of them
+fn main() {
+	let (_, address) = start_server(DEFAULT_ADDRESS).expect("Failed to start the profiler server");
+	// `save` writes into the working directory
+	let control_address = start_control_server(DEFAULT_CONTROL_ADDRESS, ".").expect("Failed to start the control endpoint");
+	println!("Streaming frames on {address} and taking commands on {control_address} for one minute");
+	let start = Instant::now();
+	let mut frame = 0;
+	while start.elapsed() < Duration::from_secs(60) {
+		{
+			scope!("frame");
+			simulate(frame);
+			render();
+		}
+		submit_frame!();
+		frame += 1;
+	}
+}
@@ -0,0 +1,35 @@
+use profiler::save_to_file;
+use profiler_attributes::profile;
+use std::time::Duration;
+
+#[profile]
+async fn handle_request(id: u64) -> u64 {
+	tokio::time::sleep(Duration::from_millis(10 * id)).await;
+	let mut sum = 0u64;
+	for i in 0..1_000_000 {
+		sum = std::hint::black_box(sum.wrapping_add(i * id));
+	}
+	tokio::time::sleep(Duration::from_millis(5)).await;
+	sum
+}
+
+fn main() {
+	let runtime = profiler::tokio::instrument_runtime(&mut tokio::runtime::Builder::new_multi_thread())
+		.worker_threads(4)
+		.enable_time()
+		.build()
+		.unwrap();
+
+	runtime.block_on(async {
+		let handles: Vec<_> = (1..=8)
+			.map(|id| profiler::tokio::spawn(format!("request {id}"), handle_request(id)))
+			.collect();
+		for handle in handles {
+			handle.await.unwrap();
+		}
+	});
+	// stopping the runtime submits the last frame of every worker
+	drop(runtime);
+
+	save_to_file!("saved.profiling");
+}
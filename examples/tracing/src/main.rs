@@ -0,0 +1,30 @@
+use profiler::{save_to_file, scope, submit_frame};
+use profiler::tracing::ProfilerLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use std::time::Duration;
+
+// stands in for a dependency that is only instrumented with tracing
+#[tracing::instrument]
+fn decode_texture(id: u32, size: u32) {
+	std::thread::sleep(Duration::from_millis(size as u64));
+	if size > 8 {
+		tracing::warn!(id, "texture is larger than expected");
+	}
+}
+
+fn main() {
+	let subscriber = tracing_subscriber::registry().with(ProfilerLayer::new());
+	tracing::subscriber::set_global_default(subscriber).unwrap();
+
+	for i in 0..4 {
+		{
+			scope!(format!("frame_{i}"));
+			for id in 0..3 {
+				decode_texture(id, 2 + 3 * id);
+			}
+		}
+		submit_frame!();
+	}
+
+	save_to_file!("saved.profiling");
+}
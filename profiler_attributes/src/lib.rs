@@ -11,7 +11,14 @@ pub fn profile(_args: TokenStream, input: TokenStream) -> TokenStream {
         _ => panic!("expected fn")
     };
     let fn_name = &fn_item.sig.ident.to_string();
-    fn_item.block.stmts.insert(0,syn::parse(quote!(let ___scope = profiler::Scope::new(concat!(concat!(module_path!(), "::"), #fn_name).to_string());).into()).unwrap());
+    if fn_item.sig.asyncness.is_some() {
+        // a scope would only cover the creation of the future, so every poll of the body is recorded instead
+        let block = &fn_item.block;
+        fn_item.block = syn::parse(quote!({ profiler::Instrumented::new(concat!(concat!(module_path!(), "::"), #fn_name), async move #block).await }).into()).unwrap();
+    }
+    else {
+        fn_item.block.stmts.insert(0,syn::parse(quote!(let ___scope = profiler::Scope::new(concat!(concat!(module_path!(), "::"), #fn_name).to_string());).into()).unwrap());
+    }
 
     use quote::ToTokens;
     item.into_token_stream().into()
@@ -0,0 +1,287 @@
+// Bevy integration, every run of a system becomes a scope on the thread that ran it
+// and every app update submits one frame per thread, numbered with bevy's frame count.
+// Worker threads of the compute task pool submit their frame of an update at its end,
+// from a task that every thread of the pool runs once, see `flush_worker_frames`.
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use bevy_app::{App, Main, Plugin};
+use bevy_diagnostic::FrameCount;
+use bevy_ecs::change_detection::{CheckChangeTicks, Tick};
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::FilteredAccessSet;
+use bevy_ecs::schedule::graph::{Dag, DiGraph};
+use bevy_ecs::schedule::{InternedSystemSet, NodeId, ScheduleBuildError, ScheduleBuildPass, ScheduleGraph, ScheduleLabel, Schedules, SystemKey, SystemSetKey};
+use bevy_ecs::system::{RunSystemError, ScheduleSystem, SystemIn, SystemParamValidationError, SystemStateFlags};
+use bevy_ecs::world::unsafe_world_cell::UnsafeWorldCell;
+use bevy_ecs::world::DeferredWorld;
+use bevy_platform::hash::FixedHasher;
+use bevy_tasks::ComputeTaskPool;
+use bevy_utils::prelude::DebugName;
+use indexmap::IndexSet;
+use profiler::{Scope, DEFAULT_FRAME_KIND, PROFILER};
+
+// Add after the plugins that create schedules, systems of schedules created later are not profiled.
+// The frame ids come from `FrameCount`, which `MinimalPlugins` and `DefaultPlugins` maintain,
+// without it the updates are counted from the first one.
+#[derive(Default)]
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<CurrentFrame>();
+		app.add_systems(Main, (begin_frame.before(Main::run_main), end_frame.after(Main::run_main)));
+	}
+
+	fn finish(&self, app: &mut App) {
+		let current_frame = app.world().resource::<CurrentFrame>().clone();
+		let mut schedules = app.world_mut().resource_mut::<Schedules>();
+		for (_, schedule) in schedules.iter_mut() {
+			// `Main` runs all other schedules from a single system, a scope around it would outlive the frame
+			if schedule.label() == Main.intern() {
+				continue;
+			}
+			schedule.add_build_pass(SystemScopes::new(current_frame.clone()));
+		}
+	}
+}
+
+// the bevy frame of the running app update and the number of threads that ran a system in it
+// and have not submitted their frame yet, shared by all systems of one app
+#[derive(Resource, Clone, Default, Debug)]
+struct CurrentFrame(Arc<FrameState>);
+
+#[derive(Default, Debug)]
+struct FrameState {
+	frame: AtomicU64,
+	pending_threads: AtomicUsize,
+}
+
+impl CurrentFrame {
+	fn get(&self) -> u64 {
+		self.0.frame.load(Ordering::Relaxed)
+	}
+
+	fn pending_threads(&self) -> usize {
+		self.0.pending_threads.load(Ordering::Acquire)
+	}
+}
+
+fn begin_frame(frame_count: Option<Res<FrameCount>>, mut updates: Local<u64>, current_frame: Res<CurrentFrame>) {
+	let frame = frame_count.map_or(*updates, |frame_count| frame_count.0 as u64);
+	*updates += 1;
+	current_frame.0.frame.store(frame, Ordering::Relaxed);
+}
+
+// `Main` runs single threaded on the thread that updates the app, it submits a frame every update
+fn end_frame(current_frame: Res<CurrentFrame>) {
+	if !flush_thread_frame(&current_frame) {
+		submit_thread_frame(current_frame.get());
+	}
+	flush_worker_frames(&current_frame);
+}
+
+// how long the end of an update waits for a worker that is busy with other tasks,
+// a worker that misses it submits its frame when it runs its next system
+const FLUSH_TIMEOUT: Duration = Duration::from_millis(20);
+
+// the frames are submitted on the threads that recorded them, so every thread of the pool gets a task.
+// A task keeps its thread until all pending frames are submitted, that way no thread takes two of them.
+fn flush_worker_frames(current_frame: &CurrentFrame) {
+	if current_frame.pending_threads() == 0 {
+		return;
+	}
+	let Some(task_pool) = ComputeTaskPool::try_get() else {
+		return;
+	};
+	let deadline = Instant::now() + FLUSH_TIMEOUT;
+	// the updating thread only waits for the tasks, it must not run one of them
+	task_pool.scope_with_executor(false, None, |scope| {
+		for _ in 0..task_pool.thread_num() {
+			scope.spawn(async move {
+				flush_thread_frame(current_frame);
+				while current_frame.pending_threads() > 0 && Instant::now() < deadline {
+					std::thread::yield_now();
+				}
+			});
+		}
+	});
+}
+
+thread_local! {
+	// the bevy frame and app the current frame of this thread belongs to
+	static THREAD_FRAME: RefCell<Option<(u64, CurrentFrame)>> = const { RefCell::new(None) };
+}
+
+fn submit_thread_frame(frame: u64) {
+	PROFILER.with_borrow_mut(|p| {
+		p.set_next_frame_id(DEFAULT_FRAME_KIND, frame);
+		p.submit_frame();
+	});
+}
+
+// submits the frame of this thread if systems of the app of `current_frame` ran on it since its last one
+fn flush_thread_frame(current_frame: &CurrentFrame) -> bool {
+	let pending = THREAD_FRAME.with_borrow_mut(|thread_frame| {
+		match thread_frame {
+			Some((_, app)) if Arc::ptr_eq(&app.0, &current_frame.0) => thread_frame.take(),
+			_ => None,
+		}
+	});
+	let Some((frame, app)) = pending else {
+		return false;
+	};
+	submit_thread_frame(frame);
+	app.0.pending_threads.fetch_sub(1, Ordering::AcqRel);
+	true
+}
+
+fn enter_frame(current_frame: &CurrentFrame) {
+	let frame = current_frame.get();
+	let previous = THREAD_FRAME.with_borrow_mut(|thread_frame| {
+		if let Some((previous, app)) = thread_frame {
+			if *previous == frame && Arc::ptr_eq(&app.0, &current_frame.0) {
+				return None;
+			}
+		}
+		current_frame.0.pending_threads.fetch_add(1, Ordering::AcqRel);
+		Some(thread_frame.replace((frame, current_frame.clone())))
+	});
+	// the frame of an update whose flush missed this thread, or of another app
+	if let Some(Some((previous, app))) = previous {
+		submit_thread_frame(previous);
+		app.0.pending_threads.fetch_sub(1, Ordering::AcqRel);
+	}
+}
+
+// wraps every system of a schedule in a `ProfiledSystem` when the schedule is built
+#[derive(Debug)]
+struct SystemScopes {
+	current_frame: CurrentFrame,
+	wrapped: HashSet<SystemKey>,
+}
+
+impl SystemScopes {
+	fn new(current_frame: CurrentFrame) -> Self {
+		Self {
+			current_frame,
+			wrapped: HashSet::new(),
+		}
+	}
+}
+
+impl ScheduleBuildPass for SystemScopes {
+	type EdgeOptions = ();
+
+	fn add_dependency(&mut self, _from: NodeId, _to: NodeId, _options: Option<&Self::EdgeOptions>) {}
+
+	fn collapse_set(
+		&mut self,
+		_set: SystemSetKey,
+		_systems: &IndexSet<SystemKey, FixedHasher>,
+		_dependency_flattening: &DiGraph<NodeId>,
+	) -> impl Iterator<Item = (NodeId, NodeId)> {
+		std::iter::empty()
+	}
+
+	fn build(&mut self, _world: &mut World, graph: &mut ScheduleGraph, _dependency_flattened: &mut Dag<SystemKey>) -> Result<(), ScheduleBuildError> {
+		// the schedule is rebuilt whenever systems are added, the ones wrapped before keep their keys
+		let keys: Vec<SystemKey> = graph.systems.iter()
+			.map(|(key, _, _)| key)
+			.filter(|key| !self.wrapped.contains(key))
+			.collect();
+		for key in keys {
+			let Some(node) = graph.systems.get_mut(key) else {
+				continue;
+			};
+			// `ApplyDeferred` only holds the place until the system is back in its wrapper
+			let system = std::mem::replace(&mut node.system, Box::new(ApplyDeferred));
+			node.system = Box::new(ProfiledSystem::new(system, self.current_frame.clone()));
+			self.wrapped.insert(key);
+		}
+		Ok(())
+	}
+}
+
+// runs `system` inside a scope named after it, everything else is passed through unchanged
+struct ProfiledSystem {
+	name: String,
+	system: ScheduleSystem,
+	current_frame: CurrentFrame,
+}
+
+impl ProfiledSystem {
+	fn new(system: ScheduleSystem, current_frame: CurrentFrame) -> Self {
+		Self {
+			name: system.name().to_string(),
+			system,
+			current_frame,
+		}
+	}
+}
+
+impl System for ProfiledSystem {
+	type In = ();
+	type Out = ();
+
+	fn name(&self) -> DebugName {
+		self.system.name()
+	}
+
+	// executors recognize `ApplyDeferred` by its type id
+	fn type_id(&self) -> TypeId {
+		self.system.type_id()
+	}
+
+	fn flags(&self) -> SystemStateFlags {
+		self.system.flags()
+	}
+
+	unsafe fn run_unsafe(&mut self, input: SystemIn<'_, Self>, world: UnsafeWorldCell) -> Result<Self::Out, RunSystemError> {
+		enter_frame(&self.current_frame);
+		let _scope = Scope::new(self.name.clone());
+		// SAFETY: the caller upholds the contract of the inner system, its access is the access of this system
+		unsafe { self.system.run_unsafe(input, world) }
+	}
+
+	#[cfg(feature = "hotpatching")]
+	fn refresh_hotpatch(&mut self) {
+		self.system.refresh_hotpatch();
+	}
+
+	fn apply_deferred(&mut self, world: &mut World) {
+		self.system.apply_deferred(world);
+	}
+
+	fn queue_deferred(&mut self, world: DeferredWorld) {
+		self.system.queue_deferred(world);
+	}
+
+	unsafe fn validate_param_unsafe(&mut self, world: UnsafeWorldCell) -> Result<(), SystemParamValidationError> {
+		// SAFETY: as for `run_unsafe`
+		unsafe { self.system.validate_param_unsafe(world) }
+	}
+
+	fn initialize(&mut self, world: &mut World) -> FilteredAccessSet {
+		self.system.initialize(world)
+	}
+
+	fn check_change_tick(&mut self, check: CheckChangeTicks) {
+		self.system.check_change_tick(check);
+	}
+
+	fn default_system_sets(&self) -> Vec<InternedSystemSet> {
+		self.system.default_system_sets()
+	}
+
+	fn get_last_run(&self) -> Tick {
+		self.system.get_last_run()
+	}
+
+	fn set_last_run(&mut self, last_run: Tick) {
+		self.system.set_last_run(last_run);
+	}
+}
@@ -0,0 +1,92 @@
+use bevy_app::{App, Update};
+use bevy_diagnostic::FrameCountPlugin;
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ExecutorKind;
+use profiler::GLOBAL_PROFILER;
+use profiler_bevy::ProfilerPlugin;
+
+fn physics() {
+	std::hint::black_box((0..1000u64).sum::<u64>());
+}
+
+fn render_prep() {}
+
+// the plugin instruments the schedules that exist when the app finishes building
+fn finish(mut app: App) -> App {
+	app.add_plugins((FrameCountPlugin, ProfilerPlugin));
+	app.finish();
+	app.cleanup();
+	app
+}
+
+#[test]
+fn system_scopes_per_update_test() {
+	let mut app = App::new();
+	app.edit_schedule(Update, |schedule| {
+		schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+	});
+	app.add_systems(Update, (physics, render_prep).chain());
+	let mut app = finish(app);
+	for _ in 0..3 {
+		app.update();
+	}
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let thread_profiler = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "system_scopes_per_update_test")
+		.unwrap();
+	let ids: Vec<u64> = thread_profiler.frames.iter().map(|frame| frame.id).collect();
+	assert_eq!(ids, vec![0, 1, 2]);
+	for frame in &thread_profiler.frames {
+		let systems: Vec<&str> = frame.scope_results.iter()
+			.filter(|scope_result| scope_result.depth == 0)
+			.map(|scope_result| scope_result.name.as_str())
+			.filter(|name| name.ends_with("physics") || name.ends_with("render_prep"))
+			.collect();
+		assert_eq!(systems, vec!["bevy::physics", "bevy::render_prep"]);
+	}
+}
+
+#[test]
+fn worker_frames_use_frame_count_test() {
+	let mut app = App::new();
+	app.add_systems(Update, (physics, physics, physics, physics));
+	let mut app = finish(app);
+	for _ in 0..4 {
+		app.update();
+	}
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frames: Vec<_> = global_profiler.thread_profilers.values()
+		.filter(|thread_profiler| thread_profiler.name.starts_with("TaskPool") || thread_profiler.name == "worker_frames_use_frame_count_test")
+		.flat_map(|thread_profiler| thread_profiler.frames.iter())
+		.collect();
+	assert!(frames.iter().all(|frame| frame.id < 4));
+	assert!(frames.iter().flat_map(|frame| frame.scope_results.iter()).any(|scope_result| scope_result.name == "bevy::physics"));
+}
+
+fn worker_system() {
+	std::hint::black_box((0..1000u64).sum::<u64>());
+}
+
+#[test]
+fn last_worker_frame_submitted_test() {
+	let mut app = App::new();
+	app.add_systems(Update, (worker_system, worker_system));
+	let mut app = finish(app);
+	for _ in 0..3 {
+		app.update();
+	}
+
+	// the frame of the last update is submitted by the end of it, not by the next system the worker runs
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let mut ids: Vec<u64> = global_profiler.thread_profilers.values()
+		.filter(|thread_profiler| thread_profiler.name.starts_with("TaskPool"))
+		.flat_map(|thread_profiler| thread_profiler.frames.iter())
+		.filter(|frame| frame.scope_results.iter().any(|scope_result| scope_result.name == "bevy::worker_system"))
+		.map(|frame| frame.id)
+		.collect();
+	ids.sort();
+	ids.dedup();
+	assert_eq!(ids, vec![0, 1, 2]);
+}
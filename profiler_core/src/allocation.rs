@@ -0,0 +1,51 @@
+use core::ops::{Add, Sub};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct AllocationStats {
+	pub allocations: u64,
+	pub deallocations: u64,
+	pub allocated_bytes: u64,
+	pub deallocated_bytes: u64,
+}
+
+impl AllocationStats {
+	pub const fn new() -> Self {
+		Self {
+			allocations: 0,
+			deallocations: 0,
+			allocated_bytes: 0,
+			deallocated_bytes: 0,
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.allocations == 0 && self.deallocations == 0
+	}
+}
+
+impl Add for AllocationStats {
+	type Output = Self;
+
+	fn add(self, other: Self) -> Self {
+		Self {
+			allocations: self.allocations + other.allocations,
+			deallocations: self.deallocations + other.deallocations,
+			allocated_bytes: self.allocated_bytes + other.allocated_bytes,
+			deallocated_bytes: self.deallocated_bytes + other.deallocated_bytes,
+		}
+	}
+}
+
+impl Sub for AllocationStats {
+	type Output = Self;
+
+	fn sub(self, other: Self) -> Self {
+		Self {
+			allocations: self.allocations.saturating_sub(other.allocations),
+			deallocations: self.deallocations.saturating_sub(other.deallocations),
+			allocated_bytes: self.allocated_bytes.saturating_sub(other.allocated_bytes),
+			deallocated_bytes: self.deallocated_bytes.saturating_sub(other.deallocated_bytes),
+		}
+	}
+}
@@ -0,0 +1,67 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+
+// something that happened at a single point in time on a thread
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Event {
+	pub name: String,
+	pub time: Duration,
+	pub kind: EventKind,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum EventKind {
+	BudgetViolation {
+		budget: Duration,
+		duration: Duration,
+	},
+	// a `profiler::sync::mpsc` message was received
+	MessageReceived {
+		latency: Duration,
+		// messages still queued after this one
+		queue_depth: u64,
+	},
+	// a tracing event or log record, the message is the event name
+	Message {
+		level: Level,
+		target: String,
+		fields: Vec<(String, String)>,
+	},
+	// recorded by the application itself, e.g. through the c api
+	Marker,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum Level {
+	Error,
+	Warn,
+	Info,
+	Debug,
+	Trace,
+}
+
+impl Level {
+	pub const ALL: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Level::Error => "ERROR",
+			Level::Warn => "WARN",
+			Level::Info => "INFO",
+			Level::Debug => "DEBUG",
+			Level::Trace => "TRACE",
+		}
+	}
+}
+
+impl Event {
+	pub fn new(name: String, time: Duration, kind: EventKind) -> Self {
+		Self {
+			name,
+			time,
+			kind,
+		}
+	}
+}
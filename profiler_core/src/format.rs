@@ -0,0 +1,118 @@
+// The `.profiling` format: `MAGIC`, the format version as a little endian u32 and a list of `GlobalProfiler`s
+// encoded with the legacy bincode configuration, the encoding of bincode 1. `profiler` streams its frames with the same encoding.
+// Files without the header are from before it was added, they hold a list of sessions or a single one.
+use alloc::vec::Vec;
+use serde::{Serialize, de::DeserializeOwned};
+pub use bincode::error::{EncodeError, DecodeError};
+use crate::GlobalProfiler;
+#[cfg(feature = "std")]
+use std::{fs::File, io::Write, path::Path};
+
+pub fn serialize<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodeError> {
+	bincode::serde::encode_to_vec(value, bincode::config::legacy())
+}
+
+// caps what the lengths in a corrupt or foreign file can make the decoder allocate,
+// far above the names and strings of any real recording
+const DECODE_LIMIT: usize = u32::MAX as usize;
+
+fn decode_config() -> impl bincode::config::Config {
+	bincode::config::legacy().with_limit::<DECODE_LIMIT>()
+}
+
+pub fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeError> {
+	bincode::serde::decode_from_slice(bytes, decode_config()).map(|(value, _)| value)
+}
+
+pub const MAGIC: [u8; 8] = *b"PROFILNG";
+// the version written by `sessions_to_binary`, files of a later version are rejected
+pub const FORMAT_VERSION: u32 = 1;
+
+// a .profiling file always holds a list of sessions, a single capture is just a list with one entry
+pub fn sessions_to_binary(sessions: &[GlobalProfiler]) -> Result<Vec<u8>, EncodeError> {
+	let mut bytes = Vec::from(MAGIC);
+	bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+	bytes.extend(serialize(sessions)?);
+	Ok(bytes)
+}
+
+pub fn sessions_from_binary(bytes: &[u8]) -> Result<Vec<GlobalProfiler>, DecodeError> {
+	let Some(versioned) = bytes.strip_prefix(&MAGIC) else {
+		return legacy_sessions_from_binary(bytes);
+	};
+	let (version, sessions) = versioned.split_first_chunk::<4>().ok_or(DecodeError::Other("truncated .profiling header"))?;
+	if u32::from_le_bytes(*version) > FORMAT_VERSION {
+		return Err(DecodeError::Other("the .profiling file was written by a newer version of the profiler"));
+	}
+	deserialize(sessions)
+}
+
+// without trailing bytes, so that the list is not mistaken for a single session or the other way around
+fn deserialize_exact<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+	match bincode::serde::decode_from_slice(bytes, decode_config()) {
+		Ok((value, read)) if read == bytes.len() => Some(value),
+		_ => None,
+	}
+}
+
+fn legacy_sessions_from_binary(bytes: &[u8]) -> Result<Vec<GlobalProfiler>, DecodeError> {
+	if let Some(sessions) = deserialize_exact(bytes) {
+		return Ok(sessions);
+	}
+	deserialize_exact::<GlobalProfiler>(bytes)
+		.map(|session| alloc::vec![session])
+		.ok_or(DecodeError::Other("not a .profiling file, or one written by an incompatible version of the profiler"))
+}
+
+#[cfg(feature = "std")]
+pub fn save_sessions_to_file<P>(sessions: &[GlobalProfiler], path: P) -> anyhow::Result<()>
+where P: AsRef<Path>
+{
+	let mut file = File::create(path)?;
+	let bytes = sessions_to_binary(sessions)?;
+	file.write_all(&bytes)?;
+	Ok(())
+}
+
+#[cfg(feature = "std")]
+pub fn load_sessions_from_file<P>(path: P) -> anyhow::Result<Vec<GlobalProfiler>>
+where P: AsRef<Path>
+{
+	let bytes = std::fs::read(path)?;
+	Ok(sessions_from_binary(&bytes)?)
+}
+
+impl GlobalProfiler {
+	// loads the first session stored in `bytes`
+	pub fn from_binary(&mut self, bytes: &[u8]) -> Result<(), DecodeError> {
+		*self = sessions_from_binary(bytes)?
+			.into_iter()
+			.next()
+			.ok_or(DecodeError::Other("no session found"))?;
+
+		Ok(())
+	}
+
+	// symbolises the samples first, see `Frame::symbolize_samples`
+	pub fn to_binary(&mut self) -> Result<Vec<u8>, EncodeError> {
+		self.symbolize_samples();
+		sessions_to_binary(core::slice::from_ref(self))
+	}
+
+	#[cfg(feature = "std")]
+	pub fn load_from_file(&mut self, filepath: &Path) -> anyhow::Result<()> {
+		let bytes = std::fs::read(filepath)?;
+		self.from_binary(&bytes)?;
+		Ok(())
+	}
+
+	#[cfg(feature = "std")]
+	pub fn save_to_file<P>(&mut self, path: P) -> anyhow::Result<()>
+	where P: AsRef<Path>
+	{
+		let mut file = File::create(path)?;
+		let bytes = self.to_binary()?;
+		file.write_all(&bytes)?;
+		Ok(())
+	}
+}
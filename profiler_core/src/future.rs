@@ -0,0 +1,33 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::GlobalProfiler;
+
+// one instrumented future from its first poll until it completed or was dropped
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TaskSpan {
+	pub id: u64,
+	pub name: String,
+	// first poll
+	pub start: Duration,
+	// wall time from the first poll until completion
+	pub duration: Duration,
+	pub polls: u64,
+	// time spent inside poll, the rest of `duration` the future was waiting
+	pub busy: Duration,
+	// false if the future was dropped before it completed
+	pub completed: bool,
+}
+
+impl GlobalProfiler {
+	// every task span of all threads, sorted by start
+	pub fn task_spans(&self) -> Vec<&TaskSpan> {
+		let mut task_spans: Vec<&TaskSpan> = self.thread_profilers.values()
+			.flat_map(|thread_profiler| thread_profiler.frames.iter())
+			.flat_map(|frame| frame.tasks.iter())
+			.collect();
+		task_spans.sort_by_key(|task_span| (task_span.start, task_span.id));
+		task_spans
+	}
+}
@@ -0,0 +1,28 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::GlobalProfiler;
+
+// a frame of the thread that defines the global frame boundaries, spanning all threads
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GlobalFrame {
+	pub id: u64,
+	pub start: Duration,
+	pub duration: Duration,
+}
+
+impl GlobalProfiler {
+	pub fn global_frames(&self) -> Vec<GlobalFrame> {
+		let mut global_frames: Vec<GlobalFrame> = self.thread_profilers.values()
+			.filter(|thread_profiler| thread_profiler.defines_global_frames)
+			.flat_map(|thread_profiler| thread_profiler.frames.iter())
+			.filter_map(|frame| Some(GlobalFrame {
+				id: frame.global_frame?,
+				start: frame.start,
+				duration: frame.duration,
+			}))
+			.collect();
+		global_frames.sort_by_key(|global_frame| global_frame.id);
+		global_frames
+	}
+}
@@ -0,0 +1,62 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::GlobalProfiler;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct IoStatistics {
+	pub reads: u64,
+	pub bytes_read: u64,
+	pub read_time: Duration,
+	pub writes: u64,
+	pub bytes_written: u64,
+	pub write_time: Duration,
+	pub flushes: u64,
+	pub flush_time: Duration,
+}
+
+impl IoStatistics {
+	pub fn add(&mut self, other: &IoStatistics) {
+		self.reads += other.reads;
+		self.bytes_read += other.bytes_read;
+		self.read_time += other.read_time;
+		self.writes += other.writes;
+		self.bytes_written += other.bytes_written;
+		self.write_time += other.write_time;
+		self.flushes += other.flushes;
+		self.flush_time += other.flush_time;
+	}
+
+	// bytes per second
+	pub fn read_throughput(&self) -> f64 {
+		throughput(self.bytes_read, self.read_time)
+	}
+
+	// bytes per second
+	pub fn write_throughput(&self) -> f64 {
+		throughput(self.bytes_written, self.write_time)
+	}
+}
+
+pub fn throughput(bytes: u64, duration: Duration) -> f64 {
+	if duration.is_zero() {
+		return 0.0;
+	}
+	bytes as f64 / duration.as_secs_f64()
+}
+
+impl GlobalProfiler {
+	// per-file statistics over all threads and frames
+	pub fn io_statistics(&self) -> BTreeMap<String, IoStatistics> {
+		let mut io_statistics: BTreeMap<String, IoStatistics> = BTreeMap::new();
+		for thread_profiler in self.thread_profilers.values() {
+			for frame in thread_profiler.frames.iter() {
+				for (name, statistics) in frame.files.iter() {
+					io_statistics.entry(name.clone()).or_default().add(statistics);
+				}
+			}
+		}
+		io_statistics
+	}
+}
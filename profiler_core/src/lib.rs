@@ -0,0 +1,153 @@
+// The data model of a recording and the `.profiling` format, shared by `profiler` and firmware or kernel style code.
+// It only needs `alloc`, the `std` feature adds process information, files and sample symbolisation.
+// `Recorder` records frames without std, with time from a user provided `Clock` into a user provided `Storage`.
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+mod format;
+pub use format::{serialize, deserialize, sessions_to_binary, sessions_from_binary, EncodeError, DecodeError, MAGIC, FORMAT_VERSION};
+#[cfg(feature = "std")]
+pub use format::{save_sessions_to_file, load_sessions_from_file};
+mod recorder;
+pub use recorder::{Clock, Storage, RingBuffer, Recorder};
+mod scope;
+pub use scope::{ScopeResult, ScopeKind, ScopeParent};
+mod event;
+pub use event::{Event, EventKind, Level};
+mod allocation;
+pub use allocation::AllocationStats;
+mod resource_usage;
+pub use resource_usage::ResourceUsage;
+mod perf_counters;
+pub use perf_counters::{PerfCounter, PerfCounters};
+mod sample;
+pub use sample::Sample;
+#[cfg(feature = "std")]
+pub use sample::set_symbolizer;
+mod sync;
+pub use sync::{LockStatistics, ChannelStatistics};
+mod io;
+pub use io::{IoStatistics, throughput};
+mod future;
+pub use future::TaskSpan;
+mod global_frame;
+pub use global_frame::GlobalFrame;
+mod statistics;
+pub use statistics::FrameStatistics;
+mod process;
+#[cfg(feature = "std")]
+pub use process::current_process_name;
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+
+pub const DEFAULT_FRAME_KIND: &str = "frame";
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Frame {
+	// sequence number, counted separately for every frame kind of a thread
+	pub id: u64,
+	pub kind: String,
+	pub label: Option<String>,
+	// the global frame that was current when this frame started
+	pub global_frame: Option<u64>,
+	pub start: Duration,
+	pub duration: Duration,
+	pub scope_results: Vec<ScopeResult>,
+	pub events: Vec<Event>,
+	// everything the thread allocated during this frame
+	pub allocations: AllocationStats,
+	// call stacks captured by the sampling profiler during this frame
+	pub samples: Vec<Sample>,
+	// contention of the `profiler::sync` locks used during this frame
+	pub locks: BTreeMap<String, LockStatistics>,
+	// traffic of the `profiler::sync::mpsc` channels used during this frame
+	pub channels: BTreeMap<String, ChannelStatistics>,
+	// traffic of the `profiler::io::Profiled` readers and writers used during this frame
+	pub files: BTreeMap<String, IoStatistics>,
+	// instrumented futures that completed or were dropped on this thread during this frame
+	pub tasks: Vec<TaskSpan>,
+}
+
+impl Frame {
+	pub fn new(start: Duration) -> Self {
+		Self {
+			id: 0,
+			kind: DEFAULT_FRAME_KIND.to_string(),
+			label: None,
+			global_frame: None,
+			start,
+			duration: Duration::from_secs(0),
+			scope_results: Vec::new(),
+			events: Vec::new(),
+			allocations: AllocationStats::new(),
+			samples: Vec::new(),
+			locks: BTreeMap::new(),
+			channels: BTreeMap::new(),
+			files: BTreeMap::new(),
+			tasks: Vec::new(),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThreadProfiler {
+	pub name: String,
+	pub frames: Vec<Frame>,
+	pub defines_global_frames: bool,
+	// id of the process the thread belongs to, see `GlobalProfiler::processes`
+	pub process: u32,
+}
+
+impl ThreadProfiler {
+	// the thread belongs to the current process, process 0 without std
+	pub fn new(name: String, frames: Vec<Frame>) -> Self {
+		Self {
+			name,
+			frames,
+			defines_global_frames: false,
+			process: process::current_process_id(),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl Default for ThreadProfiler {
+	fn default() -> Self {
+		let name = std::thread::current().name().unwrap_or("Unnamed Thread").to_string();
+		Self::new(name, Vec::new())
+	}
+}
+
+// one capture, a `.profiling` file holds a list of them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GlobalProfiler {
+	pub session_name: String,
+	pub thread_profilers: BTreeMap<u64, ThreadProfiler>,
+	// names of the processes recorded in this session by process id, more than one after `merge_process`
+	pub processes: BTreeMap<u32, String>,
+}
+
+impl GlobalProfiler {
+	pub fn new() -> Self {
+		Self::with_session_name("Unnamed Session".to_string())
+	}
+
+	// knows the current process, without std there are no processes
+	pub fn with_session_name(session_name: String) -> Self {
+		Self {
+			session_name,
+			thread_profilers: BTreeMap::new(),
+			processes: process::current_process().into_iter().collect(),
+		}
+	}
+}
+
+impl Default for GlobalProfiler {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,126 @@
+use core::ops::Sub;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfCounter {
+	Instructions,
+	Cycles,
+	CacheReferences,
+	CacheMisses,
+	BranchInstructions,
+	BranchMisses,
+	// nanoseconds
+	TaskClock,
+	PageFaults,
+	ContextSwitches,
+	CpuMigrations,
+}
+
+impl PerfCounter {
+	pub const HARDWARE: [PerfCounter; 6] = [
+		PerfCounter::Instructions,
+		PerfCounter::Cycles,
+		PerfCounter::CacheReferences,
+		PerfCounter::CacheMisses,
+		PerfCounter::BranchInstructions,
+		PerfCounter::BranchMisses,
+	];
+	pub const SOFTWARE: [PerfCounter; 4] = [
+		PerfCounter::TaskClock,
+		PerfCounter::PageFaults,
+		PerfCounter::ContextSwitches,
+		PerfCounter::CpuMigrations,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			PerfCounter::Instructions => "Instructions",
+			PerfCounter::Cycles => "Cycles",
+			PerfCounter::CacheReferences => "Cache References",
+			PerfCounter::CacheMisses => "Cache Misses",
+			PerfCounter::BranchInstructions => "Branch Instructions",
+			PerfCounter::BranchMisses => "Branch Misses",
+			PerfCounter::TaskClock => "Task Clock (ns)",
+			PerfCounter::PageFaults => "Page Faults",
+			PerfCounter::ContextSwitches => "Context Switches",
+			PerfCounter::CpuMigrations => "CPU Migrations",
+		}
+	}
+}
+
+// counters that couldn't be opened are `None`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct PerfCounters {
+	pub instructions: Option<u64>,
+	pub cycles: Option<u64>,
+	pub cache_references: Option<u64>,
+	pub cache_misses: Option<u64>,
+	pub branch_instructions: Option<u64>,
+	pub branch_misses: Option<u64>,
+	pub task_clock: Option<u64>,
+	pub page_faults: Option<u64>,
+	pub context_switches: Option<u64>,
+	pub cpu_migrations: Option<u64>,
+}
+
+impl PerfCounters {
+	pub fn get(&self, counter: PerfCounter) -> Option<u64> {
+		*self.field(counter)
+	}
+
+	pub fn set(&mut self, counter: PerfCounter, value: u64) {
+		*self.field_mut(counter) = Some(value);
+	}
+
+	fn field(&self, counter: PerfCounter) -> &Option<u64> {
+		match counter {
+			PerfCounter::Instructions => &self.instructions,
+			PerfCounter::Cycles => &self.cycles,
+			PerfCounter::CacheReferences => &self.cache_references,
+			PerfCounter::CacheMisses => &self.cache_misses,
+			PerfCounter::BranchInstructions => &self.branch_instructions,
+			PerfCounter::BranchMisses => &self.branch_misses,
+			PerfCounter::TaskClock => &self.task_clock,
+			PerfCounter::PageFaults => &self.page_faults,
+			PerfCounter::ContextSwitches => &self.context_switches,
+			PerfCounter::CpuMigrations => &self.cpu_migrations,
+		}
+	}
+
+	fn field_mut(&mut self, counter: PerfCounter) -> &mut Option<u64> {
+		match counter {
+			PerfCounter::Instructions => &mut self.instructions,
+			PerfCounter::Cycles => &mut self.cycles,
+			PerfCounter::CacheReferences => &mut self.cache_references,
+			PerfCounter::CacheMisses => &mut self.cache_misses,
+			PerfCounter::BranchInstructions => &mut self.branch_instructions,
+			PerfCounter::BranchMisses => &mut self.branch_misses,
+			PerfCounter::TaskClock => &mut self.task_clock,
+			PerfCounter::PageFaults => &mut self.page_faults,
+			PerfCounter::ContextSwitches => &mut self.context_switches,
+			PerfCounter::CpuMigrations => &mut self.cpu_migrations,
+		}
+	}
+
+	// instructions per cycle
+	pub fn ipc(&self) -> Option<f64> {
+		match (self.instructions, self.cycles) {
+			(Some(instructions), Some(cycles)) if cycles > 0 => Some(instructions as f64 / cycles as f64),
+			_ => None,
+		}
+	}
+}
+
+impl Sub for PerfCounters {
+	type Output = Self;
+
+	fn sub(self, other: Self) -> Self {
+		let mut result = PerfCounters::default();
+		for counter in PerfCounter::HARDWARE.into_iter().chain(PerfCounter::SOFTWARE) {
+			if let (Some(value), Some(other_value)) = (self.get(counter), other.get(counter)) {
+				result.set(counter, value.saturating_sub(other_value));
+			}
+		}
+		result
+	}
+}
@@ -0,0 +1,80 @@
+use alloc::string::String;
+use core::time::Duration;
+use crate::GlobalProfiler;
+
+// the executable name of this process
+#[cfg(feature = "std")]
+pub fn current_process_name() -> String {
+	std::env::current_exe().ok()
+		.and_then(|path| path.file_stem().map(|name| name.to_string_lossy().into_owned()))
+		.unwrap_or_else(|| format!("process {}", std::process::id()))
+}
+
+// 0 without std
+pub(crate) fn current_process_id() -> u32 {
+	#[cfg(feature = "std")]
+	return std::process::id();
+	#[cfg(not(feature = "std"))]
+	return 0;
+}
+
+// id and name of this process, none without std
+pub(crate) fn current_process() -> Option<(u32, String)> {
+	#[cfg(feature = "std")]
+	return Some((current_process_id(), current_process_name()));
+	#[cfg(not(feature = "std"))]
+	return None;
+}
+
+// thread and task ids are only unique within their process, FNV-1a of both
+fn process_scoped_id(process: u32, id: u64) -> u64 {
+	let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+	for byte in process.to_le_bytes().into_iter().chain(id.to_le_bytes()) {
+		hash ^= byte as u64;
+		hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+	}
+	hash
+}
+
+impl GlobalProfiler {
+	/// Adds the threads of `other`, recorded by another process on the same time base
+	pub fn merge_process(&mut self, other: GlobalProfiler) {
+		self.processes.extend(other.processes);
+		for (thread_id, mut thread_profiler) in other.thread_profilers {
+			let process = thread_profiler.process;
+			for frame in thread_profiler.frames.iter_mut() {
+				for scope_result in frame.scope_results.iter_mut() {
+					if let Some(parent) = &mut scope_result.parent {
+						parent.thread = process_scoped_id(process, parent.thread);
+					}
+					scope_result.task = scope_result.task.map(|task| process_scoped_id(process, task));
+				}
+				for task_span in frame.tasks.iter_mut() {
+					task_span.id = process_scoped_id(process, task_span.id);
+				}
+			}
+			self.thread_profilers.insert(process_scoped_id(process, thread_id), thread_profiler);
+		}
+	}
+
+	// moves everything that was recorded by `offset`, e.g. onto the time base of another process
+	pub fn shift_times(&mut self, offset: Duration) {
+		for thread_profiler in self.thread_profilers.values_mut() {
+			for frame in thread_profiler.frames.iter_mut() {
+				frame.start += offset;
+				for scope_result in frame.scope_results.iter_mut() {
+					scope_result.start += offset;
+				}
+				for event in frame.events.iter_mut() {
+					event.time += offset;
+				}
+				for sample in frame.samples.iter_mut() {
+					sample.time += offset;
+				}
+				for task_span in frame.tasks.iter_mut() {
+					task_span.start += offset;
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,145 @@
+// Records frames without std for firmware and kernel style code.
+// Time comes from a user provided `Clock` and submitted frames go to a user provided `Storage`,
+// they can be exported later in the same `.profiling` format the viewer loads, see `GlobalProfiler::to_binary`.
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
+use crate::{DEFAULT_FRAME_KIND, Event, EventKind, Frame, ScopeKind, ScopeResult};
+
+// time since an arbitrary but fixed origin, e.g. a hardware timer
+pub trait Clock {
+	fn now(&self) -> Duration;
+}
+
+impl<F: Fn() -> Duration> Clock for F {
+	fn now(&self) -> Duration {
+		self()
+	}
+}
+
+// receives every submitted frame
+pub trait Storage {
+	fn store(&mut self, frame: Frame);
+}
+
+impl Storage for Vec<Frame> {
+	fn store(&mut self, frame: Frame) {
+		self.push(frame);
+	}
+}
+
+// keeps only the latest `capacity` frames
+#[derive(Debug, Clone)]
+pub struct RingBuffer {
+	frames: VecDeque<Frame>,
+	capacity: usize,
+}
+
+impl RingBuffer {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			frames: VecDeque::with_capacity(capacity),
+			capacity,
+		}
+	}
+
+	pub fn frames(&self) -> impl Iterator<Item = &Frame> {
+		self.frames.iter()
+	}
+
+	pub fn drain(&mut self) -> impl Iterator<Item = Frame> + '_ {
+		self.frames.drain(..)
+	}
+}
+
+impl Storage for RingBuffer {
+	fn store(&mut self, frame: Frame) {
+		if self.capacity == 0 {
+			return;
+		}
+		if self.frames.len() == self.capacity {
+			self.frames.pop_front();
+		}
+		self.frames.push_back(frame);
+	}
+}
+
+// records the frames of one thread of execution
+pub struct Recorder<C, S> {
+	clock: C,
+	storage: S,
+	current_frame: Frame,
+	open_scopes: Vec<(String, Duration, ScopeKind)>,
+	next_frame_ids: BTreeMap<String, u64>,
+}
+
+impl<C: Clock, S: Storage> Recorder<C, S> {
+	pub fn new(clock: C, storage: S) -> Self {
+		let start = clock.now();
+		Self {
+			clock,
+			storage,
+			current_frame: Frame::new(start),
+			open_scopes: Vec::new(),
+			next_frame_ids: BTreeMap::new(),
+		}
+	}
+
+	pub fn begin_scope<N: Into<String>>(&mut self, name: N) {
+		self.begin_scope_with_kind(name, ScopeKind::Cpu);
+	}
+
+	pub fn begin_scope_with_kind<N: Into<String>>(&mut self, name: N, kind: ScopeKind) {
+		let start = self.clock.now();
+		self.open_scopes.push((name.into(), start, kind));
+	}
+
+	// ends the innermost open scope, does nothing if there is none
+	pub fn end_scope(&mut self) {
+		let Some((name, start, kind)) = self.open_scopes.pop() else {
+			return;
+		};
+		let end = self.clock.now();
+		let mut scope_result = ScopeResult::new(name, start, end.saturating_sub(start), self.open_scopes.len());
+		scope_result.kind = kind;
+		self.current_frame.scope_results.push(scope_result);
+	}
+
+	pub fn event<N: Into<String>>(&mut self, name: N) {
+		let time = self.clock.now();
+		self.current_frame.events.push(Event::new(name.into(), time, EventKind::Marker));
+	}
+
+	pub fn set_frame_label<L: Into<String>>(&mut self, label: L) {
+		self.current_frame.label = Some(label.into());
+	}
+
+	pub fn submit_frame(&mut self) {
+		self.submit_frame_of_kind(DEFAULT_FRAME_KIND);
+	}
+
+	// every frame kind has its own sequence of frame ids
+	pub fn submit_frame_of_kind(&mut self, kind: &str) {
+		let now = self.clock.now();
+		let next_frame_id = self.next_frame_ids.entry(kind.to_string()).or_insert(0);
+		let mut frame = core::mem::replace(&mut self.current_frame, Frame::new(now));
+		frame.id = *next_frame_id;
+		*next_frame_id += 1;
+		frame.kind = kind.to_string();
+		frame.duration = now.saturating_sub(frame.start);
+		self.storage.store(frame);
+	}
+
+	pub fn storage(&self) -> &S {
+		&self.storage
+	}
+
+	pub fn storage_mut(&mut self) -> &mut S {
+		&mut self.storage
+	}
+
+	pub fn into_storage(self) -> S {
+		self.storage
+	}
+}
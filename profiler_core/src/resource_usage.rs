@@ -0,0 +1,26 @@
+use core::ops::Sub;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct ResourceUsage {
+	pub cpu_time: Duration,
+	pub voluntary_context_switches: u64,
+	pub involuntary_context_switches: u64,
+	pub minor_page_faults: u64,
+	pub major_page_faults: u64,
+}
+
+impl Sub for ResourceUsage {
+	type Output = Self;
+
+	fn sub(self, other: Self) -> Self {
+		Self {
+			cpu_time: self.cpu_time.saturating_sub(other.cpu_time),
+			voluntary_context_switches: self.voluntary_context_switches.saturating_sub(other.voluntary_context_switches),
+			involuntary_context_switches: self.involuntary_context_switches.saturating_sub(other.involuntary_context_switches),
+			minor_page_faults: self.minor_page_faults.saturating_sub(other.minor_page_faults),
+			major_page_faults: self.major_page_faults.saturating_sub(other.major_page_faults),
+		}
+	}
+}
@@ -0,0 +1,59 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+use serde::{Serialize, Deserialize};
+use crate::{Frame, GlobalProfiler};
+
+// a call stack captured by the sampling profiler
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Sample {
+	pub time: Duration,
+	// the interrupted instruction followed by the return addresses, only valid in the process that recorded them
+	pub addresses: Vec<u64>,
+	// function names, innermost first, empty until the sample was symbolised
+	pub stack: Vec<String>,
+}
+
+impl Sample {
+	pub fn new(time: Duration, addresses: Vec<u64>) -> Self {
+		Self {
+			time,
+			addresses,
+			stack: Vec::new(),
+		}
+	}
+}
+
+// turns the addresses of samples into function names, they only mean something in the process that recorded them
+#[cfg(feature = "std")]
+static SYMBOLIZER: OnceLock<fn(&mut [Sample])> = OnceLock::new();
+
+// installed by the sampling profiler when it starts, the first symbolizer stays
+#[cfg(feature = "std")]
+pub fn set_symbolizer(symbolizer: fn(&mut [Sample])) {
+	let _ = SYMBOLIZER.set(symbolizer);
+}
+
+impl Frame {
+	// resolves the function names of the samples recorded by this process, before the frame leaves it.
+	// Does nothing until the recorder of the samples installed a symbolizer, see `set_symbolizer`
+	pub fn symbolize_samples(&mut self) {
+		#[cfg(feature = "std")]
+		if let Some(symbolize) = SYMBOLIZER.get() {
+			symbolize(&mut self.samples);
+		}
+	}
+}
+
+impl GlobalProfiler {
+	// done before the frames are saved
+	pub fn symbolize_samples(&mut self) {
+		for thread_profiler in self.thread_profilers.values_mut() {
+			for frame in thread_profiler.frames.iter_mut() {
+				frame.symbolize_samples();
+			}
+		}
+	}
+}
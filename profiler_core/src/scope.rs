@@ -0,0 +1,103 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::{AllocationStats, ResourceUsage, PerfCounters};
+
+// the scope on another thread that handed work to this one, e.g. the caller of `rayon::join`
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct ScopeParent {
+	pub thread: u64,
+	// depth of the parent scope, it was open when the child scope started
+	pub depth: usize,
+}
+
+// what a scope spent its time on, everything but `Cpu` counts as blocked
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ScopeKind {
+	#[default]
+	Cpu,
+	Wait,
+	Sleep,
+	Io,
+	Lock,
+}
+
+impl ScopeKind {
+	pub const ALL: [ScopeKind; 5] = [ScopeKind::Cpu, ScopeKind::Wait, ScopeKind::Sleep, ScopeKind::Io, ScopeKind::Lock];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			ScopeKind::Cpu => "CPU",
+			ScopeKind::Wait => "Wait",
+			ScopeKind::Sleep => "Sleep",
+			ScopeKind::Io => "I/O",
+			ScopeKind::Lock => "Lock",
+		}
+	}
+
+	pub fn is_blocked(&self) -> bool {
+		*self != ScopeKind::Cpu
+	}
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ScopeResult {
+	pub name: String,
+    pub start: Duration,
+    pub duration: Duration,
+	pub depth: usize,
+	// the global frame that was current when this scope started
+	pub global_frame: Option<u64>,
+	// allocations made while this was the innermost open scope
+	pub allocations: AllocationStats,
+	// only sampled when resource usage sampling is enabled
+	pub resource_usage: Option<ResourceUsage>,
+	// only sampled when perf counters are enabled and could be opened
+	pub perf_counters: Option<PerfCounters>,
+	// the cpu cores the scope started and ended on
+	pub cpu_start: Option<u32>,
+	pub cpu_end: Option<u32>,
+	// bytes read or written by an i/o scope
+	pub bytes: Option<u64>,
+	pub kind: ScopeKind,
+	// the instrumented future this scope polled, see `TaskSpan`
+	pub task: Option<u64>,
+	// key value pairs, e.g. the fields of a tracing span
+	pub metadata: Vec<(String, String)>,
+	// only set on top level scopes
+	pub parent: Option<ScopeParent>,
+}
+
+impl ScopeResult {
+	pub fn new(name: String, start: Duration, duration: Duration, depth: usize) -> Self {
+		Self {
+            name,
+            start,
+            duration,
+			depth,
+			global_frame: None,
+			allocations: AllocationStats::new(),
+			resource_usage: None,
+			perf_counters: None,
+			cpu_start: None,
+			cpu_end: None,
+			bytes: None,
+			kind: ScopeKind::Cpu,
+			task: None,
+			metadata: Vec::new(),
+			parent: None,
+        }
+	}
+	
+	// whether the scheduler moved the thread to another core while the scope was running
+	pub fn migrated(&self) -> bool {
+		matches!((self.cpu_start, self.cpu_end), (Some(start), Some(end)) if start != end)
+	}
+
+	pub fn is_inside(&self, other: &Self) -> bool {
+		let self_end = self.start + self.duration;
+		let other_end = other.start + other.duration;
+		self.start >= other.start && self_end <= other_end
+	}
+}
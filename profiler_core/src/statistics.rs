@@ -0,0 +1,106 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use crate::{Frame, GlobalProfiler};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameStatistics {
+	pub kind: String,
+	pub count: usize,
+	pub total: Duration,
+	pub min: Duration,
+	pub max: Duration,
+	// time spent in blocked scopes, see `Frame::blocked_time`
+	pub total_blocked: Duration,
+}
+
+impl FrameStatistics {
+	fn new(kind: String) -> Self {
+		Self {
+			kind,
+			count: 0,
+			total: Duration::from_secs(0),
+			min: Duration::MAX,
+			max: Duration::from_secs(0),
+			total_blocked: Duration::from_secs(0),
+		}
+	}
+
+	fn add(&mut self, frame: &Frame) {
+		self.count += 1;
+		self.total += frame.duration;
+		self.min = self.min.min(frame.duration);
+		self.max = self.max.max(frame.duration);
+		self.total_blocked += frame.blocked_time();
+	}
+
+	pub fn average(&self) -> Duration {
+		if self.count == 0 {
+			Duration::from_secs(0)
+		}
+		else {
+			self.total / self.count as u32
+		}
+	}
+
+	pub fn average_blocked(&self) -> Duration {
+		if self.count == 0 {
+			Duration::from_secs(0)
+		}
+		else {
+			self.total_blocked / self.count as u32
+		}
+	}
+
+	pub fn average_busy(&self) -> Duration {
+		self.average().saturating_sub(self.average_blocked())
+	}
+}
+
+impl Frame {
+	// wall time covered by wait, sleep, i/o and lock scopes, nested ones are only counted once
+	pub fn blocked_time(&self) -> Duration {
+		let mut blocked: Vec<(Duration, Duration)> = self.scope_results.iter()
+			.filter(|scope_result| scope_result.kind.is_blocked())
+			.map(|scope_result| (scope_result.start, scope_result.start + scope_result.duration))
+			.collect();
+		blocked.sort();
+
+		let mut total = Duration::from_secs(0);
+		let mut covered_until = Duration::from_secs(0);
+		for (start, end) in blocked {
+			let start = start.max(covered_until);
+			if end > start {
+				total += end - start;
+				covered_until = end;
+			}
+		}
+		total
+	}
+
+	pub fn busy_time(&self) -> Duration {
+		self.duration.saturating_sub(self.blocked_time())
+	}
+}
+
+impl GlobalProfiler {
+	pub fn frames_of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a Frame> + 'a {
+		self.thread_profilers.values()
+			.flat_map(|thread_profiler| thread_profiler.frames.iter())
+			.filter(move |frame| frame.kind == kind)
+	}
+
+	// one entry per frame kind, sorted by kind
+	pub fn frame_statistics(&self) -> Vec<FrameStatistics> {
+		let mut statistics: BTreeMap<&str, FrameStatistics> = BTreeMap::new();
+		for thread_profiler in self.thread_profilers.values() {
+			for frame in thread_profiler.frames.iter() {
+				statistics.entry(&frame.kind)
+					.or_insert_with(|| FrameStatistics::new(frame.kind.clone()))
+					.add(frame);
+			}
+		}
+		statistics.into_values().collect()
+	}
+}
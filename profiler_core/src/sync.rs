@@ -0,0 +1,82 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::GlobalProfiler;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct LockStatistics {
+	pub acquisitions: u64,
+	// acquisitions that had to wait for another thread
+	pub contended_acquisitions: u64,
+	pub total_wait: Duration,
+	pub max_wait: Duration,
+	pub total_held: Duration,
+}
+
+impl LockStatistics {
+	pub fn add(&mut self, other: &LockStatistics) {
+		self.acquisitions += other.acquisitions;
+		self.contended_acquisitions += other.contended_acquisitions;
+		self.total_wait += other.total_wait;
+		self.max_wait = self.max_wait.max(other.max_wait);
+		self.total_held += other.total_held;
+	}
+}
+
+impl GlobalProfiler {
+	// per-lock statistics over all threads and frames
+	pub fn lock_contention(&self) -> BTreeMap<String, LockStatistics> {
+		let mut lock_contention: BTreeMap<String, LockStatistics> = BTreeMap::new();
+		for thread_profiler in self.thread_profilers.values() {
+			for frame in thread_profiler.frames.iter() {
+				for (name, statistics) in frame.locks.iter() {
+					lock_contention.entry(name.clone()).or_default().add(statistics);
+				}
+			}
+		}
+		lock_contention
+	}
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct ChannelStatistics {
+	pub sent: u64,
+	pub received: u64,
+	// time between send and receive of the received messages
+	pub total_latency: Duration,
+	pub max_latency: Duration,
+	pub max_queue_depth: u64,
+}
+
+impl ChannelStatistics {
+	pub fn add(&mut self, other: &ChannelStatistics) {
+		self.sent += other.sent;
+		self.received += other.received;
+		self.total_latency += other.total_latency;
+		self.max_latency = self.max_latency.max(other.max_latency);
+		self.max_queue_depth = self.max_queue_depth.max(other.max_queue_depth);
+	}
+
+	pub fn average_latency(&self) -> Duration {
+		if self.received == 0 {
+			return Duration::ZERO;
+		}
+		self.total_latency.div_f64(self.received as f64)
+	}
+}
+
+impl GlobalProfiler {
+	// per-channel statistics over all threads and frames
+	pub fn channel_statistics(&self) -> BTreeMap<String, ChannelStatistics> {
+		let mut channel_statistics: BTreeMap<String, ChannelStatistics> = BTreeMap::new();
+		for thread_profiler in self.thread_profilers.values() {
+			for frame in thread_profiler.frames.iter() {
+				for (name, statistics) in frame.channels.iter() {
+					channel_statistics.entry(name.clone()).or_default().add(statistics);
+				}
+			}
+		}
+		channel_statistics
+	}
+}
@@ -0,0 +1,112 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use profiler_core::{Frame, Recorder, RingBuffer, ScopeKind, EventKind, GlobalProfiler, ThreadProfiler, DEFAULT_FRAME_KIND, sessions_to_binary, sessions_from_binary, serialize, MAGIC, FORMAT_VERSION};
+
+// advances by one millisecond every time it is read
+fn fake_clock() -> impl Fn() -> Duration {
+	let ticks = Rc::new(Cell::new(0));
+	move || {
+		ticks.set(ticks.get() + 1);
+		Duration::from_millis(ticks.get())
+	}
+}
+
+#[test]
+fn recorder_test() {
+	let mut recorder = Recorder::new(fake_clock(), Vec::<Frame>::new());
+	recorder.begin_scope("main_loop");
+	recorder.begin_scope_with_kind("wait_for_dma", ScopeKind::Wait);
+	recorder.end_scope();
+	recorder.event("irq");
+	recorder.end_scope();
+	// unmatched ends are ignored
+	recorder.end_scope();
+	recorder.set_frame_label("boot");
+	recorder.submit_frame();
+	recorder.submit_frame_of_kind("isr");
+	recorder.submit_frame();
+
+	let frames = recorder.into_storage();
+	assert_eq!(frames.len(), 3);
+	assert_eq!((frames[0].kind.as_str(), frames[0].id), ("frame", 0));
+	assert_eq!((frames[1].kind.as_str(), frames[1].id), ("isr", 0));
+	assert_eq!((frames[2].kind.as_str(), frames[2].id), ("frame", 1));
+	assert_eq!(frames[0].label.as_deref(), Some("boot"));
+	assert_eq!(frames[0].start, Duration::from_millis(1));
+	assert_eq!(frames[0].duration, Duration::from_millis(6));
+	assert_eq!(frames[0].scope_results[0].name, "wait_for_dma");
+	assert_eq!(frames[0].scope_results[0].depth, 1);
+	assert_eq!(frames[0].scope_results[0].duration, Duration::from_millis(1));
+	assert_eq!(frames[0].scope_results[1].name, "main_loop");
+	assert_eq!(frames[0].scope_results[1].depth, 0);
+	assert_eq!(frames[0].events[0].time, Duration::from_millis(5));
+}
+
+#[test]
+fn ring_buffer_test() {
+	let mut recorder = Recorder::new(fake_clock(), RingBuffer::new(2));
+	for _ in 0..5 {
+		recorder.submit_frame();
+	}
+	let ids: Vec<u64> = recorder.storage_mut().drain().map(|frame| frame.id).collect();
+	assert_eq!(ids, vec![3, 4]);
+	assert_eq!(recorder.storage().frames().count(), 0);
+}
+
+#[test]
+fn session_round_trip_test() {
+	let mut recorder = Recorder::new(fake_clock(), Vec::<Frame>::new());
+	recorder.begin_scope("update");
+	recorder.begin_scope_with_kind("flash_read", ScopeKind::Io);
+	recorder.end_scope();
+	recorder.end_scope();
+	recorder.event("watchdog");
+	recorder.set_frame_label("first");
+	recorder.submit_frame();
+
+	let mut session = GlobalProfiler::with_session_name("firmware".to_string());
+	session.thread_profilers.insert(7, ThreadProfiler::new("core 0".to_string(), recorder.into_storage()));
+	session.thread_profilers.insert(8, ThreadProfiler::new("core 1".to_string(), Vec::new()));
+	let bytes = session.to_binary().unwrap();
+
+	let loaded = sessions_from_binary(&bytes).unwrap();
+	assert_eq!(loaded.len(), 1);
+	assert_eq!(loaded[0].session_name, "firmware");
+	assert_eq!(loaded[0].thread_profilers.len(), 2);
+	assert!(loaded[0].thread_profilers[&8].frames.is_empty());
+	let thread_profiler = &loaded[0].thread_profilers[&7];
+	assert_eq!(thread_profiler.name, "core 0");
+	let frame = &thread_profiler.frames[0];
+	assert_eq!(frame.kind, DEFAULT_FRAME_KIND);
+	assert_eq!(frame.label.as_deref(), Some("first"));
+	assert_eq!(frame.start, Duration::from_millis(1));
+	assert_eq!(frame.scope_results.len(), 2);
+	assert_eq!(frame.scope_results[0].name, "flash_read");
+	assert_eq!(frame.scope_results[0].kind, ScopeKind::Io);
+	assert_eq!(frame.scope_results[0].depth, 1);
+	assert_eq!(frame.scope_results[1].name, "update");
+	assert_eq!(frame.scope_results[1].kind, ScopeKind::Cpu);
+	assert_eq!(frame.events[0].name, "watchdog");
+	assert_eq!(frame.events[0].kind, EventKind::Marker);
+	assert_eq!(sessions_to_binary(&loaded).unwrap(), bytes);
+}
+
+#[test]
+fn format_version_test() {
+	let session = GlobalProfiler::with_session_name("current".to_string());
+	let bytes = sessions_to_binary(core::slice::from_ref(&session)).unwrap();
+	assert!(bytes.starts_with(&MAGIC));
+	assert_eq!(sessions_from_binary(&bytes).unwrap()[0].session_name, "current");
+
+	// files from before the header, with a list of sessions or a single one
+	let list = serialize(&vec![session.clone(), session.clone()]).unwrap();
+	assert_eq!(sessions_from_binary(&list).unwrap().len(), 2);
+	let single = serialize(&session).unwrap();
+	assert_eq!(sessions_from_binary(&single).unwrap()[0].session_name, "current");
+
+	let mut newer = bytes.clone();
+	newer[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+	assert!(sessions_from_binary(&newer).is_err());
+	assert!(sessions_from_binary(b"not a recording").is_err());
+}
@@ -0,0 +1,91 @@
+// Generates `profiler.h` in `OUT_DIR` from the `#[no_mangle] extern "C"` functions in `src/lib.rs`.
+// Build scripts must not modify the package sources, `tests/header.rs` checks that `include/profiler.h` matches it.
+use std::fmt::Write;
+
+fn main() {
+	println!("cargo:rerun-if-changed=src/lib.rs");
+
+	let source = std::fs::read_to_string("src/lib.rs").unwrap();
+	let file = syn::parse_file(&source).unwrap();
+
+	let mut header = String::new();
+	header.push_str("// Generated by build.rs from src/lib.rs, do not edit.\n");
+	header.push_str("#ifndef PROFILER_H\n#define PROFILER_H\n\n");
+	header.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n");
+	for item in file.items.iter() {
+		let syn::Item::Fn(function) = item else {
+			continue;
+		};
+		if function.sig.abi.is_none() || !function.attrs.iter().any(|attr| attr.path().is_ident("no_mangle")) {
+			continue;
+		}
+
+		header.push('\n');
+		for line in doc_lines(&function.attrs) {
+			writeln!(header, "//{line}").unwrap();
+		}
+		let parameters: Vec<String> = function.sig.inputs.iter()
+			.map(|input| match input {
+				syn::FnArg::Typed(parameter) => {
+					let syn::Pat::Ident(name) = &*parameter.pat else {
+						panic!("unsupported parameter pattern in {}", function.sig.ident);
+					};
+					let ty = c_type(&parameter.ty);
+					if ty.ends_with('*') {
+						format!("{ty}{}", name.ident)
+					}
+					else {
+						format!("{ty} {}", name.ident)
+					}
+				},
+				syn::FnArg::Receiver(_) => panic!("unexpected self parameter in {}", function.sig.ident),
+			})
+			.collect();
+		let return_type = match &function.sig.output {
+			syn::ReturnType::Default => "void".to_string(),
+			syn::ReturnType::Type(_, ty) => c_type(ty),
+		};
+		let parameters = if parameters.is_empty() { "void".to_string() } else { parameters.join(", ") };
+		writeln!(header, "{return_type} {}({parameters});", function.sig.ident).unwrap();
+	}
+	header.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n#endif\n");
+
+	// only written when it changed, so that the header isn't touched on every build
+	let path = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("profiler.h");
+	if std::fs::read_to_string(&path).ok().as_deref() != Some(header.as_str()) {
+		std::fs::write(path, header).unwrap();
+	}
+}
+
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+	attrs.iter()
+		.filter(|attr| attr.path().is_ident("doc"))
+		.filter_map(|attr| match &attr.meta {
+			syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }), .. }) => Some(doc.value()),
+			_ => None,
+		})
+		.collect()
+}
+
+fn c_type(ty: &syn::Type) -> String {
+	match ty {
+		syn::Type::Ptr(pointer) => {
+			let pointee = c_type(&pointer.elem);
+			if pointer.const_token.is_some() {
+				format!("const {pointee} *")
+			}
+			else {
+				format!("{pointee} *")
+			}
+		},
+		syn::Type::Path(path) => {
+			let name = path.path.segments.last().unwrap().ident.to_string();
+			match name.as_str() {
+				"c_char" => "char",
+				"c_int" => "int",
+				_ => panic!("no c type for {name}"),
+			}.to_string()
+		},
+		_ => panic!("unsupported type in the c api"),
+	}
+}
@@ -0,0 +1,42 @@
+// Generated by build.rs from src/lib.rs, do not edit.
+#ifndef PROFILER_H
+#define PROFILER_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Begins a scope on the calling thread, it ends with the matching `profiler_end`.
+// Returns 0 on success and -1 on failure.
+//
+// # Safety
+// `name` must be null or a valid nul terminated string, it is copied.
+int profiler_begin(const char *name);
+
+// Ends the innermost scope begun with `profiler_begin` on the calling thread.
+// Returns 0 on success and -1 on failure, e.g. if there is no such scope.
+int profiler_end(void);
+
+// Ends the current frame of the calling thread.
+// Returns 0 on success and -1 on failure.
+int profiler_submit_frame(void);
+
+// Records an event at the current time in the current frame of the calling thread.
+// Returns 0 on success and -1 on failure.
+//
+// # Safety
+// `name` must be null or a valid nul terminated string, it is copied.
+int profiler_event(const char *name);
+
+// Saves every submitted frame of all threads to `path`.
+// Returns 0 on success and -1 on failure.
+//
+// # Safety
+// `path` must be null or a valid nul terminated string.
+int profiler_save(const char *path);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
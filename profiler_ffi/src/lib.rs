@@ -0,0 +1,109 @@
+// C api for instrumenting C and C++ code that is linked into a profiled Rust program.
+// Everything is recorded into the same thread local `PROFILER` and `GLOBAL_PROFILER` as the Rust macros,
+// so C scopes nest with the Rust scopes of the same thread.
+// The header `include/profiler.h` is generated from this file by `build.rs`.
+// Every function returns 0 on success and -1 on failure, a panic must not unwind into the C caller.
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use profiler::{EventKind, Scope, GLOBAL_PROFILER, PROFILER};
+
+thread_local! {
+	// scopes begun from C on this thread, innermost last
+	static C_SCOPES: RefCell<CScopes> = const { RefCell::new(CScopes(Vec::new())) };
+}
+
+// scopes that were not ended when the thread exits end with it
+struct CScopes(Vec<Scope>);
+
+impl Drop for CScopes {
+	fn drop(&mut self) {
+		while let Some(scope) = self.0.pop() {
+			scope.end_in_teardown();
+		}
+	}
+}
+
+// a panic, e.g. a profiler that is already borrowed by the caller, is reported as a failure
+fn catch_panic(f: impl FnOnce() -> c_int) -> c_int {
+	catch_unwind(AssertUnwindSafe(f)).unwrap_or(-1)
+}
+
+unsafe fn to_string(name: *const c_char) -> Option<String> {
+	if name.is_null() {
+		return None;
+	}
+	Some(unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned())
+}
+
+/// Begins a scope on the calling thread, it ends with the matching `profiler_end`.
+/// Returns 0 on success and -1 on failure.
+///
+/// # Safety
+/// `name` must be null or a valid nul terminated string, it is copied.
+#[no_mangle]
+pub unsafe extern "C" fn profiler_begin(name: *const c_char) -> c_int {
+	let Some(name) = (unsafe { to_string(name) }) else {
+		return -1;
+	};
+	catch_panic(|| {
+		let scope = Scope::new(name);
+		C_SCOPES.with_borrow_mut(|scopes| scopes.0.push(scope));
+		0
+	})
+}
+
+/// Ends the innermost scope begun with `profiler_begin` on the calling thread.
+/// Returns 0 on success and -1 on failure, e.g. if there is no such scope.
+#[no_mangle]
+pub extern "C" fn profiler_end() -> c_int {
+	catch_panic(|| {
+		let Some(scope) = C_SCOPES.with_borrow_mut(|scopes| scopes.0.pop()) else {
+			return -1;
+		};
+		drop(scope);
+		0
+	})
+}
+
+/// Ends the current frame of the calling thread.
+/// Returns 0 on success and -1 on failure.
+#[no_mangle]
+pub extern "C" fn profiler_submit_frame() -> c_int {
+	catch_panic(|| {
+		PROFILER.with_borrow_mut(|p| p.submit_frame());
+		0
+	})
+}
+
+/// Records an event at the current time in the current frame of the calling thread.
+/// Returns 0 on success and -1 on failure.
+///
+/// # Safety
+/// `name` must be null or a valid nul terminated string, it is copied.
+#[no_mangle]
+pub unsafe extern "C" fn profiler_event(name: *const c_char) -> c_int {
+	let Some(name) = (unsafe { to_string(name) }) else {
+		return -1;
+	};
+	catch_panic(|| {
+		PROFILER.with_borrow_mut(|p| p.push_event(name, EventKind::Marker));
+		0
+	})
+}
+
+/// Saves every submitted frame of all threads to `path`.
+/// Returns 0 on success and -1 on failure.
+///
+/// # Safety
+/// `path` must be null or a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn profiler_save(path: *const c_char) -> c_int {
+	let Some(path) = (unsafe { to_string(path) }) else {
+		return -1;
+	};
+	catch_panic(|| match GLOBAL_PROFILER.lock().unwrap().save_to_file(&path) {
+		Ok(()) => 0,
+		Err(_) => -1,
+	})
+}
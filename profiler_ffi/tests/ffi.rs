@@ -0,0 +1,51 @@
+use profiler::{EventKind, GLOBAL_PROFILER, PROFILER, Scope};
+use profiler_ffi::{profiler_begin, profiler_end, profiler_event, profiler_save, profiler_submit_frame};
+
+#[test]
+fn c_and_rust_scopes_nest_test() {
+	{
+		let _rust_scope = Scope::new("rust".to_string());
+		assert_eq!(unsafe { profiler_begin(c"c++ physics".as_ptr()) }, 0);
+		assert_eq!(unsafe { profiler_event(c"contact".as_ptr()) }, 0);
+		{
+			let _rust_callback = Scope::new("rust callback".to_string());
+		}
+		assert_eq!(profiler_end(), 0);
+		// unmatched ends and null names fail without recording anything
+		assert_eq!(unsafe { profiler_begin(std::ptr::null()) }, -1);
+	}
+	assert_eq!(profiler_end(), -1);
+	// a panic inside the profiler is reported instead of unwinding into C
+	assert_eq!(PROFILER.with_borrow_mut(|_| profiler_submit_frame()), -1);
+	assert_eq!(profiler_submit_frame(), 0);
+
+	{
+		let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+		let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+		let scopes: Vec<(&str, usize)> = frame.scope_results.iter()
+			.map(|scope_result| (scope_result.name.as_str(), scope_result.depth))
+			.collect();
+		assert_eq!(scopes, vec![("rust callback", 2), ("c++ physics", 1), ("rust", 0)]);
+		assert_eq!(frame.events.len(), 1);
+		assert_eq!(frame.events[0].name, "contact");
+		assert_eq!(frame.events[0].kind, EventKind::Marker);
+	}
+
+	let path = std::env::temp_dir().join(format!("profiler_ffi_test_{}.profiling", std::process::id()));
+	let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+	assert_eq!(unsafe { profiler_save(c_path.as_ptr()) }, 0);
+	let loaded = profiler::load_sessions_from_file(&path).unwrap();
+	assert_eq!(loaded[0].thread_profilers.len(), 1);
+	std::fs::remove_file(&path).unwrap();
+	assert_eq!(unsafe { profiler_save(c"/nonexistent/dir/file.profiling".as_ptr()) }, -1);
+}
+
+// a scope that C never ended ends with its thread, also when the profiler of the thread is destroyed first
+#[test]
+fn scope_open_at_thread_exit_test() {
+	std::thread::spawn(|| {
+		// looks up the C scopes before the profiler of the thread exists
+		assert_eq!(profiler_end(), -1);
+		assert_eq!(unsafe { profiler_begin(c"never ended".as_ptr()) }, 0);
+	}).join().unwrap();
+}
@@ -0,0 +1,11 @@
+// the checked in header has to match the one `build.rs` generates from the current source
+#[test]
+fn header_up_to_date_test() {
+	let generated = include_str!(concat!(env!("OUT_DIR"), "/profiler.h"));
+	let checked_in = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/include/profiler.h")).unwrap();
+	assert!(
+		checked_in == generated,
+		"include/profiler.h is out of date, copy {}/profiler.h over it",
+		env!("OUT_DIR"),
+	);
+}
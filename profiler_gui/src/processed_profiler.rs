@@ -1,27 +1,163 @@
-use std::{collections::HashMap, time::Duration};
-use profiler::ThreadProfiler;
+use std::{collections::{BTreeMap, HashMap}, time::Duration};
+use profiler::{Frame, FrameStatistics, GlobalFrame, GlobalProfiler, ScopeResult, TaskSpan, ThreadProfiler};
+use profiler::sync::{LockStatistics, mpsc::ChannelStatistics};
+use profiler::io::IoStatistics;
+
+// scopes gathered from every thread, e.g. all that started on one cpu core
+#[derive(Debug, Clone)]
+pub struct Lane {
+	pub thread_profiler: ThreadProfiler,
+	// the thread every frame of `thread_profiler` was recorded on
+	pub frame_thread_names: Vec<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct ProcessedGlobalProfiler {
+	pub start_time: Duration,
 	pub total_time: Duration,
-	pub thread_profilers: HashMap<u64, ThreadProfiler>,
+	pub thread_profilers: BTreeMap<u64, ThreadProfiler>,
+	// by process id
+	pub processes: BTreeMap<u32, String>,
+	pub frame_statistics: Vec<FrameStatistics>,
+	pub lock_contention: BTreeMap<String, LockStatistics>,
+	pub channel_statistics: BTreeMap<String, ChannelStatistics>,
+	pub io_statistics: BTreeMap<String, IoStatistics>,
+	// by task id
+	pub task_spans: HashMap<u64, TaskSpan>,
+	pub global_frames: Vec<GlobalFrame>,
+	pub max_scope_allocated_bytes: u64,
+	pub cpu_lanes: Vec<Lane>,
+	// one lane per instrumented future, sorted by its first poll
+	pub task_lanes: Vec<Lane>,
 }
 
 impl ProcessedGlobalProfiler {
-	pub fn new(thread_profilers: HashMap<u64, ThreadProfiler>) -> Self {
-		let mut total_time = Duration::from_secs(0);
+	pub fn new(global_profiler: GlobalProfiler) -> Self {
+		let frame_statistics = global_profiler.frame_statistics();
+		let lock_contention = global_profiler.lock_contention();
+		let channel_statistics = global_profiler.channel_statistics();
+		let io_statistics = global_profiler.io_statistics();
+		let sorted_task_spans: Vec<TaskSpan> = global_profiler.task_spans().into_iter().cloned().collect();
+		let global_frames = global_profiler.global_frames();
+		let thread_profilers = global_profiler.thread_profilers;
+		let processes = global_profiler.processes;
+		let mut start_time = None;
+		let mut end_time = Duration::from_secs(0);
+		let mut max_scope_allocated_bytes = 0;
 		for thread_profiler in thread_profilers.values() {
-			for scope_result in thread_profiler.frames.last().unwrap().scope_results.iter() {
-				let end_time = scope_result.start + scope_result.duration;
-				if total_time < end_time {
-					total_time = end_time;
+			for frame in thread_profiler.frames.iter() {
+				if start_time.is_none_or(|start_time| frame.start < start_time) {
+					start_time = Some(frame.start);
+				}
+				for scope_result in frame.scope_results.iter() {
+					let scope_end_time = scope_result.start + scope_result.duration;
+					if end_time < scope_end_time {
+						end_time = scope_end_time;
+					}
+					max_scope_allocated_bytes = max_scope_allocated_bytes.max(scope_result.allocations.allocated_bytes);
 				}
 			}
 		}
+		let start_time = start_time.unwrap_or_default().min(end_time);
+		let cpu_lanes = Self::cpu_lanes(&thread_profilers);
+		let task_lanes = Self::task_lanes(&thread_profilers, &sorted_task_spans);
+		let task_spans = sorted_task_spans.into_iter()
+			.map(|task_span| (task_span.id, task_span))
+			.collect();
 
 		Self {
-			total_time,
+			start_time,
+			total_time: end_time - start_time,
 			thread_profilers,
+			processes,
+			frame_statistics,
+			lock_contention,
+			channel_statistics,
+			io_statistics,
+			task_spans,
+			global_frames,
+			max_scope_allocated_bytes,
+			cpu_lanes,
+			task_lanes,
+		}
+	}
+
+	fn cpu_lanes(thread_profilers: &BTreeMap<u64, ThreadProfiler>) -> Vec<Lane> {
+		let mut cpu_lanes: BTreeMap<u32, Lane> = BTreeMap::new();
+		for thread_profiler in thread_profilers.values() {
+			for frame in thread_profiler.frames.iter() {
+				let mut frames_by_cpu: BTreeMap<u32, Frame> = BTreeMap::new();
+				for scope_result in frame.scope_results.iter() {
+					let Some(cpu) = scope_result.cpu_start else {
+						continue;
+					};
+					frames_by_cpu.entry(cpu)
+						.or_insert_with(|| Frame { scope_results: Vec::new(), events: Vec::new(), ..frame.clone() })
+						.scope_results.push(scope_result.clone());
+				}
+				for (cpu, cpu_frame) in frames_by_cpu {
+					let cpu_lane = cpu_lanes.entry(cpu).or_insert_with(|| Lane {
+						thread_profiler: ThreadProfiler::new(format!("CPU {cpu}"), Vec::new()),
+						frame_thread_names: Vec::new(),
+					});
+					cpu_lane.thread_profiler.frames.push(cpu_frame);
+					cpu_lane.frame_thread_names.push(thread_profiler.name.clone());
+				}
+			}
 		}
+		cpu_lanes.into_values().collect()
 	}
-}
\ No newline at end of file
+
+	// every poll of a task with the scopes nested inside it, from whichever thread ran the poll
+	fn task_lanes(thread_profilers: &BTreeMap<u64, ThreadProfiler>, task_spans: &[TaskSpan]) -> Vec<Lane> {
+		let mut task_lanes: HashMap<u64, Lane> = task_spans.iter()
+			.map(|task_span| (task_span.id, Lane {
+				thread_profiler: ThreadProfiler::new(format!("{} #{}", task_span.name, task_span.id), Vec::new()),
+				frame_thread_names: Vec::new(),
+			}))
+			.collect();
+		for thread_profiler in thread_profilers.values() {
+			for frame in thread_profiler.frames.iter() {
+				let mut frames_by_task: BTreeMap<u64, Frame> = BTreeMap::new();
+				for poll in frame.scope_results.iter() {
+					let Some(task) = poll.task else {
+						continue;
+					};
+					let task_frame = frames_by_task.entry(task)
+						.or_insert_with(|| Frame { scope_results: Vec::new(), events: Vec::new(), ..frame.clone() });
+					// polls start at the top of the lane
+					for scope_result in frame.scope_results.iter() {
+						if scope_result.depth >= poll.depth && scope_result.is_inside(poll) {
+							let mut scope_result = scope_result.clone();
+							scope_result.depth -= poll.depth;
+							task_frame.scope_results.push(scope_result);
+						}
+					}
+				}
+				for (task, task_frame) in frames_by_task {
+					if let Some(task_lane) = task_lanes.get_mut(&task) {
+						task_lane.thread_profiler.frames.push(task_frame);
+						task_lane.frame_thread_names.push(thread_profiler.name.clone());
+					}
+				}
+			}
+		}
+		task_spans.iter()
+			.filter_map(|task_span| task_lanes.remove(&task_span.id))
+			.collect()
+	}
+
+	// the scope on another thread that handed the work to `scope_result`
+	pub fn parent_scope(&self, scope_result: &ScopeResult) -> Option<(&ThreadProfiler, &ScopeResult)> {
+		let parent = scope_result.parent?;
+		let thread_profiler = self.thread_profilers.get(&parent.thread)?;
+		let parent_scope = thread_profiler.frames.iter()
+			.flat_map(|frame| frame.scope_results.iter())
+			.find(|candidate| candidate.depth == parent.depth && candidate.start <= scope_result.start && scope_result.start <= candidate.start + candidate.duration)?;
+		Some((thread_profiler, parent_scope))
+	}
+
+	pub fn frame_statistics_of_kind(&self, kind: &str) -> Option<&FrameStatistics> {
+		self.frame_statistics.iter().find(|statistics| statistics.kind == kind)
+	}
+}
@@ -1,9 +1,67 @@
 use eframe::egui;
-use profiler::{GlobalProfiler, ThreadProfiler, GLOBAL_PROFILER};
+use profiler::perf_counters::PerfCounter;
+use profiler::{Event, EventKind, Level, Sample, GlobalFrame, GlobalProfiler, ThreadProfiler, GLOBAL_PROFILER};
+use profiler::stream::{read_message, add_stream_message, load_stream_from_file, StreamMessage};
+use profiler::control::{send_command, ControlCommand};
 use std::{path::Path, time::Duration, collections::HashMap};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
 use crate::ProcessedGlobalProfiler;
 use crate::utils::draw_truncated_text;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorMode {
+	Uniform,
+	AllocationVolume,
+	CpuUtilization,
+	InstructionsPerCycle,
+}
+
+impl ColorMode {
+	const ALL: [ColorMode; 4] = [ColorMode::Uniform, ColorMode::AllocationVolume, ColorMode::CpuUtilization, ColorMode::InstructionsPerCycle];
+
+	fn name(&self) -> &'static str {
+		match self {
+			ColorMode::Uniform => "Uniform",
+			ColorMode::AllocationVolume => "Allocation volume",
+			ColorMode::CpuUtilization => "CPU time / wall time",
+			ColorMode::InstructionsPerCycle => "Instructions per cycle",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+	Threads,
+	CpuCores,
+	Tasks,
+}
+
+impl Layout {
+	const ALL: [Layout; 3] = [Layout::Threads, Layout::CpuCores, Layout::Tasks];
+
+	fn name(&self) -> &'static str {
+		match self {
+			Layout::Threads => "By thread",
+			Layout::CpuCores => "By CPU core",
+			Layout::Tasks => "By task",
+		}
+	}
+}
+
+// frames of a process streaming to this viewer through `profiler::server`
+struct LiveConnection {
+	address: String,
+	messages: Receiver<StreamMessage>,
+	global_profiler: GlobalProfiler,
+	connected: bool,
+}
+
+impl LiveConnection {
+	// older frames are dropped to keep the viewer responsive
+	const MAX_FRAMES_PER_THREAD: usize = 300;
+}
+
 pub struct Viewer {
 	show_open_file_dialog: bool,
 	loading_error_msg: Option<String>,
@@ -13,7 +71,23 @@ pub struct Viewer {
 	view_height: f64,
 	mouse_pos: egui::Pos2,
 	profiler: Option<ProcessedGlobalProfiler>,
+	sessions: Vec<GlobalProfiler>,
+	selected_session: usize,
+	selected_global_frame: Option<u64>,
+	color_mode: ColorMode,
+	layout: Layout,
+	// least severe message level that is shown, `None` hides all messages
+	message_level: Option<Level>,
 	thread_profilers_collapsed: HashMap<String, bool>,
+	connect_address: String,
+	live: Option<LiveConnection>,
+	// inputs of the remote control panel
+	control_address: String,
+	control_frames: u64,
+	control_filter: String,
+	control_path: String,
+	// outcome of the last command sent
+	control_result: Option<String>,
 }
 
 impl Viewer {
@@ -27,13 +101,26 @@ impl Viewer {
             view_height: 600.0,
             mouse_pos: egui::Pos2::new(0.0, 0.0),
             profiler: None,
+			sessions: Vec::new(),
+			selected_session: 0,
+			selected_global_frame: None,
+			color_mode: ColorMode::Uniform,
+			layout: Layout::Threads,
+			message_level: Some(Level::Trace),
 			thread_profilers_collapsed: HashMap::new(),
+			connect_address: profiler::server::DEFAULT_ADDRESS.to_string(),
+			live: None,
+			control_address: profiler::control::DEFAULT_CONTROL_ADDRESS.to_string(),
+			control_frames: 100,
+			control_filter: String::new(),
+			control_path: "remote.profiling".to_string(),
+			control_result: None,
         }
 	}
 
 	fn calc_pos_x(&self, x: f64) -> f64 {
 		if let Some(profiler) = &self.profiler {
-			let relative_pos = x / profiler.total_time.as_secs_f64();
+			let relative_pos = (x - profiler.start_time.as_secs_f64()) / profiler.total_time.as_secs_f64();
 			(self.view_start * (1.0 - relative_pos) + self.view_end * relative_pos) * self.view_width
 		}
 		else {
@@ -43,25 +130,41 @@ impl Viewer {
 
 	pub fn update_embedded(&mut self) {
 		self.show_open_file_dialog = false;
-		let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+		let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+		// only the frames that are shown
+		for thread_profiler in global_profiler.thread_profilers.values_mut() {
+			if let Some(frame) = thread_profiler.frames.last_mut() {
+				frame.symbolize_samples();
+			}
+		}
 		let mut global_profiler_current_frame = GlobalProfiler::new();
+		global_profiler_current_frame.processes = global_profiler.processes.clone();
 		for (thread_id, thread_profiler) in &global_profiler.thread_profilers {
 			let frames = if let Some(frame) = thread_profiler.frames.last() {
 				let mut modified_frame = frame.clone();
 				for scope_result in &mut modified_frame.scope_results {
 					scope_result.start -= modified_frame.start;
 				}
+				for event in &mut modified_frame.events {
+					event.time -= modified_frame.start;
+				}
+				for sample in &mut modified_frame.samples {
+					sample.time = sample.time.saturating_sub(modified_frame.start);
+				}
 				modified_frame.start = Duration::from_secs(0);
 				vec![modified_frame]
 			}
 			else {
 				Vec::new()
 			};
-			global_profiler_current_frame.thread_profilers.insert(*thread_id, ThreadProfiler::new(thread_profiler.name.clone(), frames));
+			let mut thread_profiler_current_frame = ThreadProfiler::new(thread_profiler.name.clone(), frames);
+			thread_profiler_current_frame.defines_global_frames = thread_profiler.defines_global_frames;
+			thread_profiler_current_frame.process = thread_profiler.process;
+			global_profiler_current_frame.thread_profilers.insert(*thread_id, thread_profiler_current_frame);
 		}
 		self.view_start = 0.0;
 		self.view_end = 1.0;
-		self.profiler = Some(ProcessedGlobalProfiler::new(global_profiler_current_frame.thread_profilers));
+		self.profiler = Some(ProcessedGlobalProfiler::new(global_profiler_current_frame));
 	}
 
 	pub fn update(&mut self, ui: &mut egui::Ui) {
@@ -73,10 +176,37 @@ impl Viewer {
 
 		self.handle_input(ui.ctx());
 
+		self.receive_live_frames(ui.ctx());
+
+		if let Some(live) = &self.live {
+			if live.connected {
+				ui.label(format!("Live: {}", live.address));
+			}
+			else {
+				ui.label(format!("Disconnected from {}", live.address));
+			}
+		}
+
+		self.draw_remote_control(ui);
+
 		if self.profiler.is_none() {
 			return;
 		}
 
+		ui.horizontal(|ui| {
+			if self.sessions.len() > 1 {
+				self.draw_session_selector(ui);
+			}
+			self.draw_color_mode_selector(ui);
+			self.draw_layout_selector(ui);
+			self.draw_message_level_selector(ui);
+		});
+
+		self.draw_frame_statistics(ui);
+		self.draw_lock_contention(ui);
+		self.draw_channel_statistics(ui);
+		self.draw_io_statistics(ui);
+
 		self.view_width = ui.available_width() as f64;
 		self.view_height = ui.available_height() as f64;
 		let rounding = 2.5;
@@ -95,15 +225,41 @@ impl Viewer {
 
 				let profiler = self.profiler.as_ref().unwrap();
 
+				let mut clicked_global_frame = None;
+				if !profiler.global_frames.is_empty() {
+					clicked_global_frame = self.draw_global_frames(ui, &profiler.global_frames, &mut cursor_y, canvas);
+				}
+				let thread_profilers_top = cursor_y;
+
 				let mut selection_rect = None;
-				for thread_profiler in profiler.thread_profilers.values() {
+				let lanes: Vec<(&ThreadProfiler, Option<&[String]>)> = match self.layout {
+					Layout::Threads => {
+						let mut thread_profilers: Vec<&ThreadProfiler> = profiler.thread_profilers.values().collect();
+						thread_profilers.sort_by_key(|thread_profiler| thread_profiler.process);
+						thread_profilers.into_iter().map(|thread_profiler| (thread_profiler, None)).collect()
+					},
+					Layout::CpuCores => profiler.cpu_lanes.iter().map(|cpu_lane| (&cpu_lane.thread_profiler, Some(cpu_lane.frame_thread_names.as_slice()))).collect(),
+					Layout::Tasks => profiler.task_lanes.iter().map(|task_lane| (&task_lane.thread_profiler, Some(task_lane.frame_thread_names.as_slice()))).collect(),
+				};
+				// threads of merged child processes are grouped under the name of their process
+				let mut current_process = None;
+				for (thread_profiler, frame_thread_names) in lanes {
+					if self.layout == Layout::Threads && profiler.processes.len() > 1 && current_process != Some(thread_profiler.process) {
+						current_process = Some(thread_profiler.process);
+						let process_name = profiler.processes.get(&thread_profiler.process).map_or("Unknown process", String::as_str);
+						self.draw_process_name(ui, process_name, thread_profiler.process, &mut cursor_y, canvas);
+					}
 					let mut collapsed = self.thread_profilers_collapsed.get(&thread_profiler.name).copied().unwrap_or(false);
-					self.draw_thread_profiler(ui, thread_profiler, &mut selection_rect, &mut cursor_y, &mut collapsed, canvas, rounding, hover_rect_offset);
+					self.draw_thread_profiler(ui, thread_profiler, frame_thread_names, &mut selection_rect, &mut cursor_y, &mut collapsed, canvas, rounding, hover_rect_offset);
 					self.thread_profilers_collapsed.insert(thread_profiler.name.clone(), collapsed);
 				}
+				self.draw_global_frame_boundaries(ui, &profiler.global_frames, thread_profilers_top, cursor_y, canvas);
 				if let Some(selection_rect) = selection_rect {
 					ui.painter().with_clip_rect(canvas).rect_stroke(selection_rect, rounding, egui::Stroke::new(2.0 * hover_rect_offset, egui::Color32::YELLOW));
 				}
+				if let Some(global_frame) = clicked_global_frame {
+					self.select_global_frame(global_frame);
+				}
 				let mut used_rect = canvas;
 				used_rect.max.y = cursor_y as f32;
 				used_rect.max.y = used_rect.max.y.max(used_rect.min.y + available_height);
@@ -112,8 +268,77 @@ impl Viewer {
 		});
 	}
 
+	// returns the id of the global frame that was clicked
+	fn draw_global_frames(&self, ui: &mut egui::Ui, global_frames: &[GlobalFrame], cursor_y: &mut f64, canvas: egui::Rect) -> Option<u64> {
+		let height = 20.0;
+		let mut clicked_global_frame = None;
+		for global_frame in global_frames.iter() {
+			let x = self.calc_pos_x(global_frame.start.as_secs_f64());
+			let width = self.calc_pos_x((global_frame.start + global_frame.duration).as_secs_f64()) - x;
+			if x > self.view_width || x + width < 0.0 {
+				continue;
+			}
+
+			let rect = egui::Rect::from_min_size(egui::pos2((x + canvas.min.x as f64) as f32, *cursor_y as f32), egui::vec2(width as f32, height));
+			let response = ui.interact(rect, egui::Id::new(("global_frame", global_frame.id)), egui::Sense::click());
+			let color = if self.selected_global_frame == Some(global_frame.id) {
+				egui::Color32::from_rgb(120, 100, 20)
+			}
+			else if response.hovered() {
+				egui::Color32::from_gray(90)
+			}
+			else {
+				egui::Color32::from_gray(50)
+			};
+			ui.painter().with_clip_rect(rect.intersect(canvas)).rect(rect, 0.0, color, egui::Stroke::new(1.0, egui::Color32::BLACK));
+			if width > 10.0 {
+				draw_truncated_text(ui, &format!("#{}", global_frame.id), width as f32, rect.center(), rect.intersect(canvas));
+			}
+			if response.clicked() {
+				clicked_global_frame = Some(global_frame.id);
+			}
+		}
+		*cursor_y += height as f64;
+		clicked_global_frame
+	}
+
+	fn draw_global_frame_boundaries(&self, ui: &mut egui::Ui, global_frames: &[GlobalFrame], top: f64, bottom: f64, canvas: egui::Rect) {
+		let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(60));
+		for global_frame in global_frames.iter() {
+			let x = (self.calc_pos_x(global_frame.start.as_secs_f64()) + canvas.min.x as f64) as f32;
+			ui.painter().with_clip_rect(canvas).vline(x, (top as f32)..=(bottom as f32), stroke);
+		}
+	}
+
+	// zooms to the global frame, selecting it again clears the selection
+	fn select_global_frame(&mut self, id: u64) {
+		if self.selected_global_frame == Some(id) {
+			self.selected_global_frame = None;
+			self.view_start = 0.0;
+			self.view_end = 1.0;
+			return;
+		}
+
+		let Some(profiler) = &self.profiler else {
+			return;
+		};
+		let Some(global_frame) = profiler.global_frames.iter().find(|global_frame| global_frame.id == id) else {
+			return;
+		};
+		let total_time = profiler.total_time.as_secs_f64();
+		let relative_start = (global_frame.start.as_secs_f64() - profiler.start_time.as_secs_f64()) / total_time;
+		let relative_width = global_frame.duration.as_secs_f64() / total_time;
+		if relative_width <= 0.0 {
+			return;
+		}
+		self.selected_global_frame = Some(id);
+		let view_size = 1.0 / relative_width;
+		self.view_start = -relative_start * view_size;
+		self.view_end = self.view_start + view_size;
+	}
+
 	#[allow(clippy::too_many_arguments)]
-	fn draw_thread_profiler(&self, ui: &mut egui::Ui, thread_profiler: &profiler::ThreadProfiler, selecton_rect: &mut Option<egui::Rect>, cursor_y: &mut f64, collapsed: &mut bool, canvas: egui::Rect, rounding: f32, hover_rect_offset: f32) {
+	fn draw_thread_profiler(&self, ui: &mut egui::Ui, thread_profiler: &profiler::ThreadProfiler, frame_thread_names: Option<&[String]>, selecton_rect: &mut Option<egui::Rect>, cursor_y: &mut f64, collapsed: &mut bool, canvas: egui::Rect, rounding: f32, hover_rect_offset: f32) {
 		let function_height = 28.0;
 		let text_height = 15.0;
 		let seperator_size = 1.0;
@@ -125,7 +350,8 @@ impl Viewer {
 		let mut largest_frame_height = 0.0;
 
 		if !*collapsed {
-			for frame in thread_profiler.frames.iter() {
+			for (frame_index, frame) in thread_profiler.frames.iter().enumerate() {
+				let thread_name = frame_thread_names.map_or(&thread_profiler.name, |names| &names[frame_index]);
 				let frame_start_pixel = self.calc_pos_x(frame.start.as_secs_f64());
 				let frame_end_pixel = self.calc_pos_x((frame.start + frame.duration).as_secs_f64());
 				if (frame_start_pixel + canvas.min.x as f64) > self.view_width && (frame_end_pixel + canvas.min.x as f64) < 0.0 {
@@ -148,21 +374,38 @@ impl Viewer {
 					let x = local_x + canvas.min.x as f64;
 					let rect = egui::Rect::from_min_size(egui::Pos2::new(x as f32, y as f32), egui::Vec2::new(width as f32, function_height as f32));
 					let painter = ui.painter().with_clip_rect(rect.intersect(canvas));
+					let color = self.scope_color(scope_result);
+					// scopes that moved to another core while running are outlined
+					let stroke = if scope_result.migrated() {
+						egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 140, 0))
+					}
+					else {
+						egui::Stroke::new(1.5, egui::Color32::BLACK)
+					};
 					if width > 10.0 {
-						painter.rect(rect, rounding, egui::Color32::BLUE, egui::Stroke::new(1.5, egui::Color32::BLACK));
+						painter.rect(rect, rounding, color, stroke);
+						if scope_result.kind.is_blocked() {
+							draw_hatching(&painter, rect);
+						}
 						draw_truncated_text(ui, &scope_result.name, width as f32, rect.center(), rect.intersect(canvas));
 					}
 					else {
-						painter.rect_filled(rect, 0.0, egui::Color32::BLUE);
+						painter.rect_filled(rect, 0.0, color);
 					}
 					
 					let hovered: bool = self.mouse_pos.x as f64 >= x && self.mouse_pos.y as f64 >= y && self.mouse_pos.y as f64 <= y + function_height && self.mouse_pos.x as f64 <= x + width;
 					if hovered {
 						*selecton_rect = Some(egui::Rect::from_min_size(rect.min - egui::Vec2::new(hover_rect_offset, hover_rect_offset), rect.size() + egui::Vec2::new(2.0 * hover_rect_offset, 2.0 * hover_rect_offset)));
 						
-						self.draw_tooltip(ui.ctx(), scope_result, frame, &thread_profiler.name);
+						self.draw_tooltip(ui.ctx(), scope_result, frame, thread_name);
 					}
 				}
+
+				self.draw_samples(ui, &frame.samples, thread_name_height, text_height * 1.5, canvas);
+
+				for event in frame.events.iter().filter(|event| self.is_event_shown(event)) {
+					self.draw_event(ui, event, thread_name_height + text_height * 0.75, canvas);
+				}
 			}
 		}
 		let text = format!("{} {}", if *collapsed { "⏵" } else { "⏷" }, thread_profiler.name);
@@ -195,10 +438,94 @@ impl Viewer {
 		*cursor_y += largest_frame_height;
 	}
 
-	fn draw_tooltip(&self, ctx: &egui::Context, scope_result: &profiler::ScopeResult, frame: &profiler::Frame, thread_name: &String) {
+	fn draw_process_name(&self, ui: &mut egui::Ui, name: &str, process: u32, cursor_y: &mut f64, canvas: egui::Rect) {
+		let text_height = 15.0;
+		let pos = egui::pos2(canvas.min.x, (*cursor_y + text_height * 0.25) as f32);
+		ui.painter().with_clip_rect(canvas).text(pos, egui::Align2::LEFT_TOP, format!("{} ({})", name, process), egui::FontId::proportional(text_height as f32 * 1.2), egui::Color32::WHITE);
+		*cursor_y += text_height * 1.75;
+	}
+
+	// a small tick for every sample along the thread name row
+	fn draw_samples(&self, ui: &mut egui::Ui, samples: &[Sample], top: f64, height: f64, canvas: egui::Rect) {
+		let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(40));
+		for sample in samples.iter() {
+			let x = self.calc_pos_x(sample.time.as_secs_f64());
+			if x < 0.0 || x > self.view_width {
+				continue;
+			}
+			let x = (x + canvas.min.x as f64) as f32;
+			ui.painter().with_clip_rect(canvas).vline(x, ((top + height * 0.75) as f32)..=((top + height) as f32), stroke);
+		}
+	}
+
+	fn draw_event(&self, ui: &mut egui::Ui, event: &Event, y: f64, canvas: egui::Rect) {
+		let radius = 4.0;
+		let x = self.calc_pos_x(event.time.as_secs_f64());
+		if x < 0.0 || x > self.view_width {
+			return;
+		}
+
+		let center = egui::pos2((x + canvas.min.x as f64) as f32, y as f32);
+		ui.painter().with_clip_rect(canvas).circle(center, radius, event_color(&event.kind), egui::Stroke::new(1.0, egui::Color32::BLACK));
+		if self.mouse_pos.distance(center) <= radius * 2.0 {
+			egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("profiler_event_tooltip"), |ui| {
+				ui.label(&event.name);
+				match &event.kind {
+					EventKind::BudgetViolation { budget, duration } => {
+						ui.label(format!("Duration: {}", format_duration(duration)));
+						ui.label(format!("Budget: {}", format_duration(budget)));
+					},
+					EventKind::MessageReceived { latency, queue_depth } => {
+						ui.label(format!("Queue latency: {}", format_duration(latency)));
+						ui.label(format!("Queue depth: {queue_depth}"));
+					},
+					EventKind::Marker => {},
+					EventKind::Message { level, target, fields } => {
+						ui.label(format!("{} {}", level.name(), target));
+						for (key, value) in fields.iter() {
+							ui.label(format!("{key}: {value}"));
+						}
+					},
+				}
+			});
+		}
+	}
+
+	// the functions the sampling profiler caught running inside the scope
+	fn draw_sample_summary(&self, ui: &mut egui::Ui, scope_result: &profiler::ScopeResult, frame: &profiler::Frame) {
+		let scope_end = scope_result.start + scope_result.duration;
+		let mut sample_count = 0;
+		let mut functions: HashMap<&str, usize> = HashMap::new();
+		for sample in frame.samples.iter().filter(|sample| sample.time >= scope_result.start && sample.time <= scope_end) {
+			sample_count += 1;
+			if let Some(function) = sample.stack.first() {
+				*functions.entry(function).or_insert(0) += 1;
+			}
+		}
+		if sample_count == 0 {
+			return;
+		}
+
+		let mut functions: Vec<(&str, usize)> = functions.into_iter().collect();
+		functions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+		ui.separator();
+		ui.label(format!("Samples: {}", sample_count));
+		for (function, count) in functions.iter().take(5) {
+			ui.label(format!("{:.0}% {}", 100.0 * *count as f32 / sample_count as f32, function));
+		}
+		ui.separator();
+	}
+
+	fn draw_tooltip(&self, ctx: &egui::Context, scope_result: &profiler::ScopeResult, frame: &profiler::Frame, thread_name: &str) {
 		egui::show_tooltip_at_pointer(ctx, egui::Id::new("profiler_result_tooltip"), |ui| {
 			ui.label(&scope_result.name);
 			ui.label(format!("Duration: {}", format_duration(&scope_result.duration)));
+			if scope_result.kind.is_blocked() {
+				ui.label(format!("Blocked: {}", scope_result.kind.name()));
+			}
+			if let Some(task_span) = scope_result.task.and_then(|task| self.profiler.as_ref()?.task_spans.get(&task)) {
+				ui.label(format!("Task #{}: {} over {} polls ({} busy){}", task_span.id, format_duration(&task_span.duration), task_span.polls, format_duration(&task_span.busy), if task_span.completed { "" } else { ", dropped" }));
+			}
 
 			let mut self_duration = scope_result.duration;
 			for i in 0..frame.scope_results.len() {
@@ -208,6 +535,59 @@ impl Viewer {
 			}
 			ui.label(format!("Self Duration: {}", format_duration(&self_duration)));
 			ui.label(format!("Thread: {}", thread_name));
+			self.draw_sample_summary(ui, scope_result, frame);
+			if let Some(resource_usage) = &scope_result.resource_usage {
+				ui.label(format!("CPU Time: {} ({:.0}% of wall time)", format_duration(&resource_usage.cpu_time), 100.0 * cpu_utilization(scope_result).unwrap_or(0.0)));
+				ui.label(format!("Context Switches: {} voluntary, {} involuntary", resource_usage.voluntary_context_switches, resource_usage.involuntary_context_switches));
+				ui.label(format!("Page Faults: {} minor, {} major", resource_usage.minor_page_faults, resource_usage.major_page_faults));
+			}
+			if let Some(perf_counters) = &scope_result.perf_counters {
+				for counter in PerfCounter::HARDWARE.into_iter().chain(PerfCounter::SOFTWARE) {
+					if let Some(value) = perf_counters.get(counter) {
+						ui.label(format!("{}: {}", counter.name(), value));
+					}
+				}
+				if let Some(ipc) = perf_counters.ipc() {
+					ui.label(format!("IPC: {ipc:.2}"));
+				}
+			}
+			if let Some((thread_profiler, parent_scope)) = self.profiler.as_ref().and_then(|p| p.parent_scope(scope_result)) {
+				ui.label(format!("Started by: {} on {}", parent_scope.name, thread_profiler.name));
+			}
+			for (key, value) in scope_result.metadata.iter() {
+				ui.label(format!("{key}: {value}"));
+			}
+			if let Some(bytes) = scope_result.bytes {
+				ui.label(format!("Transferred: {} ({}/s)", format_bytes(bytes), format_bytes(profiler::io::throughput(bytes, scope_result.duration) as u64)));
+			}
+			if !scope_result.allocations.is_empty() {
+				ui.label(format!("Allocations: {} ({})", scope_result.allocations.allocations, format_bytes(scope_result.allocations.allocated_bytes)));
+				ui.label(format!("Deallocations: {} ({})", scope_result.allocations.deallocations, format_bytes(scope_result.allocations.deallocated_bytes)));
+			}
+			match (scope_result.cpu_start, scope_result.cpu_end) {
+				(Some(cpu_start), Some(cpu_end)) if scope_result.migrated() => ui.label(format!("CPU: {} → {} (migrated)", cpu_start, cpu_end)),
+				(Some(cpu), _) => ui.label(format!("CPU: {}", cpu)),
+				_ => ui.label("CPU: unknown"),
+			};
+			if let Some(global_frame) = scope_result.global_frame {
+				ui.label(format!("Global Frame: #{}", global_frame));
+			}
+			ui.separator();
+			match &frame.label {
+				Some(label) => ui.label(format!("Frame: {} #{} ({})", frame.kind, frame.id, label)),
+				None => ui.label(format!("Frame: {} #{}", frame.kind, frame.id)),
+			};
+			let frame_duration = format_duration(&frame.duration);
+			if let Some(statistics) = self.profiler.as_ref().and_then(|p| p.frame_statistics_of_kind(&frame.kind)) {
+				ui.label(format!("Frame Duration: {} (average {})", frame_duration, format_duration(&statistics.average())));
+			}
+			else {
+				ui.label(format!("Frame Duration: {}", frame_duration));
+			}
+			ui.label(format!("Frame Busy: {} / Blocked: {}", format_duration(&frame.busy_time()), format_duration(&frame.blocked_time())));
+			if !frame.allocations.is_empty() {
+				ui.label(format!("Frame Allocations: {} ({})", frame.allocations.allocations, format_bytes(frame.allocations.allocated_bytes)));
+			}
 		});
 	}
 
@@ -279,22 +659,357 @@ impl Viewer {
 		self.view_end -= (self.view_end - zoom_target) * amount;
 	}
 
-	fn load_profiler(&mut self, filepath: &Path) {
-		let mut loaded_profiler = GlobalProfiler::new();
-		if let Err(e) = loaded_profiler.load_from_file(filepath) {
-			self.loading_error_msg = Some(e.to_string());
-			self.show_open_file_dialog = true;
+	fn draw_frame_statistics(&self, ui: &mut egui::Ui) {
+		let profiler = self.profiler.as_ref().unwrap();
+		egui::CollapsingHeader::new("Frame statistics").show(ui, |ui| {
+			egui::Grid::new("frame_statistics").striped(true).show(ui, |ui| {
+				ui.label("Kind");
+				ui.label("Count");
+				ui.label("Average");
+				ui.label("Min");
+				ui.label("Max");
+				ui.label("Average busy");
+				ui.label("Average blocked");
+				ui.end_row();
+				for statistics in profiler.frame_statistics.iter() {
+					ui.label(&statistics.kind);
+					ui.label(statistics.count.to_string());
+					ui.label(format_duration(&statistics.average()));
+					ui.label(format_duration(&statistics.min));
+					ui.label(format_duration(&statistics.max));
+					ui.label(format_duration(&statistics.average_busy()));
+					ui.label(format_duration(&statistics.average_blocked()));
+					ui.end_row();
+				}
+			});
+		});
+	}
+
+	fn draw_lock_contention(&self, ui: &mut egui::Ui) {
+		let profiler = self.profiler.as_ref().unwrap();
+		if profiler.lock_contention.is_empty() {
+			return;
+		}
+		egui::CollapsingHeader::new("Lock contention").show(ui, |ui| {
+			egui::Grid::new("lock_contention").striped(true).show(ui, |ui| {
+				ui.label("Lock");
+				ui.label("Acquisitions");
+				ui.label("Contended");
+				ui.label("Total wait");
+				ui.label("Max wait");
+				ui.label("Total held");
+				ui.end_row();
+				for (name, statistics) in profiler.lock_contention.iter() {
+					ui.label(name);
+					ui.label(statistics.acquisitions.to_string());
+					ui.label(statistics.contended_acquisitions.to_string());
+					ui.label(format_duration(&statistics.total_wait));
+					ui.label(format_duration(&statistics.max_wait));
+					ui.label(format_duration(&statistics.total_held));
+					ui.end_row();
+				}
+			});
+		});
+	}
+
+	fn draw_channel_statistics(&self, ui: &mut egui::Ui) {
+		let profiler = self.profiler.as_ref().unwrap();
+		if profiler.channel_statistics.is_empty() {
+			return;
+		}
+		egui::CollapsingHeader::new("Channels").show(ui, |ui| {
+			egui::Grid::new("channel_statistics").striped(true).show(ui, |ui| {
+				ui.label("Channel");
+				ui.label("Sent");
+				ui.label("Received");
+				ui.label("Average latency");
+				ui.label("Max latency");
+				ui.label("Max queue depth");
+				ui.end_row();
+				for (name, statistics) in profiler.channel_statistics.iter() {
+					ui.label(name);
+					ui.label(statistics.sent.to_string());
+					ui.label(statistics.received.to_string());
+					ui.label(format_duration(&statistics.average_latency()));
+					ui.label(format_duration(&statistics.max_latency));
+					ui.label(statistics.max_queue_depth.to_string());
+					ui.end_row();
+				}
+			});
+		});
+	}
+
+	fn draw_io_statistics(&self, ui: &mut egui::Ui) {
+		let profiler = self.profiler.as_ref().unwrap();
+		if profiler.io_statistics.is_empty() {
+			return;
+		}
+		egui::CollapsingHeader::new("I/O").show(ui, |ui| {
+			egui::Grid::new("io_statistics").striped(true).show(ui, |ui| {
+				ui.label("File");
+				ui.label("Reads");
+				ui.label("Read");
+				ui.label("Read throughput");
+				ui.label("Writes");
+				ui.label("Written");
+				ui.label("Write throughput");
+				ui.label("Flushes");
+				ui.end_row();
+				for (name, statistics) in profiler.io_statistics.iter() {
+					ui.label(name);
+					ui.label(statistics.reads.to_string());
+					ui.label(format_bytes(statistics.bytes_read));
+					ui.label(format!("{}/s", format_bytes(statistics.read_throughput() as u64)));
+					ui.label(statistics.writes.to_string());
+					ui.label(format_bytes(statistics.bytes_written));
+					ui.label(format!("{}/s", format_bytes(statistics.write_throughput() as u64)));
+					ui.label(format!("{} ({})", statistics.flushes, format_duration(&statistics.flush_time)));
+					ui.end_row();
+				}
+			});
+		});
+	}
+
+	fn draw_layout_selector(&mut self, ui: &mut egui::Ui) {
+		egui::ComboBox::from_label("Layout")
+			.selected_text(self.layout.name())
+			.show_ui(ui, |ui| {
+				for layout in Layout::ALL {
+					ui.selectable_value(&mut self.layout, layout, layout.name());
+				}
+			});
+	}
+
+	fn draw_message_level_selector(&mut self, ui: &mut egui::Ui) {
+		egui::ComboBox::from_label("Messages")
+			.selected_text(self.message_level.map_or("Off", |level| level.name()))
+			.show_ui(ui, |ui| {
+				ui.selectable_value(&mut self.message_level, None, "Off");
+				for level in Level::ALL {
+					ui.selectable_value(&mut self.message_level, Some(level), level.name());
+				}
+			});
+	}
+
+	fn is_event_shown(&self, event: &Event) -> bool {
+		match &event.kind {
+			EventKind::Message { level, .. } => self.message_level.is_some_and(|message_level| *level <= message_level),
+			_ => true,
+		}
+	}
+
+	fn draw_color_mode_selector(&mut self, ui: &mut egui::Ui) {
+		egui::ComboBox::from_label("Color")
+			.selected_text(self.color_mode.name())
+			.show_ui(ui, |ui| {
+				for color_mode in ColorMode::ALL {
+					ui.selectable_value(&mut self.color_mode, color_mode, color_mode.name());
+				}
+			});
+	}
+
+	fn scope_color(&self, scope_result: &profiler::ScopeResult) -> egui::Color32 {
+		let color = match self.color_mode {
+			ColorMode::Uniform => egui::Color32::BLUE,
+			ColorMode::AllocationVolume => {
+				let max_allocated_bytes = self.profiler.as_ref().map_or(0, |p| p.max_scope_allocated_bytes);
+				// logarithmic, so that a few huge allocations don't hide everything else
+				let t = if max_allocated_bytes == 0 {
+					0.0
+				}
+				else {
+					((scope_result.allocations.allocated_bytes as f64).ln_1p() / (max_allocated_bytes as f64).ln_1p()) as f32
+				};
+				egui::Color32::from_rgb((255.0 * t) as u8, 0, (255.0 * (1.0 - t)) as u8)
+			},
+			// green when computing, red when blocked or preempted, gray when not sampled
+			ColorMode::CpuUtilization => match cpu_utilization(scope_result) {
+				Some(utilization) => egui::Color32::from_rgb((255.0 * (1.0 - utilization)) as u8, (200.0 * utilization) as u8, 0),
+				None => egui::Color32::GRAY,
+			},
+			// red when stalled on memory, green when the cpu is kept busy
+			ColorMode::InstructionsPerCycle => match scope_result.perf_counters.as_ref().and_then(|perf_counters| perf_counters.ipc()) {
+				Some(ipc) => {
+					let t = (ipc / 4.0).min(1.0) as f32;
+					egui::Color32::from_rgb((255.0 * (1.0 - t)) as u8, (200.0 * t) as u8, 0)
+				},
+				None => egui::Color32::GRAY,
+			},
+		};
+		// blocked scopes are dimmed, so that they don't look like heavy computation
+		let color = if scope_result.kind.is_blocked() {
+			color.gamma_multiply(0.4)
+		}
+		else {
+			color
+		};
+		if self.selected_global_frame.is_some_and(|id| scope_result.global_frame != Some(id)) {
+			color.gamma_multiply(0.3)
 		}
 		else {
-			self.loading_error_msg = None;
-			let global_profiler = ProcessedGlobalProfiler::new(loaded_profiler.thread_profilers);
-			for thread_profiler in global_profiler.thread_profilers.values() {
-				self.thread_profilers_collapsed.insert(thread_profiler.name.clone(), false);
+			color
+		}
+	}
+
+	fn draw_session_selector(&mut self, ui: &mut egui::Ui) {
+		let mut selected_session = self.selected_session;
+		egui::ComboBox::from_label("Session")
+			.selected_text(&self.sessions[selected_session].session_name)
+			.show_ui(ui, |ui| {
+				for (i, session) in self.sessions.iter().enumerate() {
+					ui.selectable_value(&mut selected_session, i, &session.session_name);
+				}
+			});
+		if selected_session != self.selected_session {
+			self.select_session(selected_session);
+		}
+	}
+
+	fn select_session(&mut self, index: usize) {
+		self.selected_session = index;
+		self.selected_global_frame = None;
+		let global_profiler = ProcessedGlobalProfiler::new(self.sessions[index].clone());
+		for thread_profiler in global_profiler.thread_profilers.values() {
+			self.thread_profilers_collapsed.insert(thread_profiler.name.clone(), false);
+		}
+		self.profiler = Some(global_profiler);
+		self.view_start  = 0.0;
+		self.view_end = 1.0;
+	}
+
+	fn load_profiler(&mut self, filepath: &Path) {
+		// files written by a `FileStreamSink` hold a single session
+		let sessions = profiler::load_sessions_from_file(filepath)
+			.or_else(|e| load_stream_from_file(filepath).map(|session| vec![session]).map_err(|_| e));
+		match sessions {
+			Ok(sessions) if !sessions.is_empty() => {
+				self.live = None;
+				self.loading_error_msg = None;
+				self.sessions = sessions;
+				self.select_session(0);
+				self.show_open_file_dialog = false;
+			},
+			Ok(_) => {
+				self.loading_error_msg = Some("File contains no sessions".to_string());
+				self.show_open_file_dialog = true;
+			},
+			Err(e) => {
+				self.loading_error_msg = Some(e.to_string());
+				self.show_open_file_dialog = true;
+			},
+		}
+	}
+
+	/// Shows the frames streamed by the `profiler::server` at `address` as they arrive
+	pub fn connect(&mut self, address: &str) {
+		let mut stream = match TcpStream::connect(address) {
+			Ok(stream) => stream,
+			Err(e) => {
+				self.loading_error_msg = Some(format!("Failed to connect to {}: {}", address, e));
+				self.show_open_file_dialog = true;
+				return;
+			},
+		};
+		let (sender, messages) = channel();
+		std::thread::spawn(move || {
+			// ends when the server closes the connection or the viewer is gone
+			while let Ok(Some(message)) = read_message(&mut stream) {
+				if sender.send(message).is_err() {
+					break;
+				}
 			}
-			self.profiler = Some(global_profiler);
-			self.view_start  = 0.0;
-			self.view_end = 1.0;
-			self.show_open_file_dialog = false;
+		});
+		self.live = Some(LiveConnection {
+			address: address.to_string(),
+			messages,
+			global_profiler: GlobalProfiler::with_session_name(format!("Live {}", address)),
+			connected: true,
+		});
+		self.sessions.clear();
+		self.profiler = None;
+		self.loading_error_msg = None;
+		self.show_open_file_dialog = false;
+	}
+
+	// sends commands to the control endpoint of a running process, see `profiler::control`
+	fn draw_remote_control(&mut self, ui: &mut egui::Ui) {
+		egui::CollapsingHeader::new("Remote control").show(ui, |ui| {
+			let mut command = None;
+			ui.horizontal(|ui| {
+				ui.label("Address");
+				ui.text_edit_singleline(&mut self.control_address);
+			});
+			ui.horizontal(|ui| {
+				if ui.button("Start recording").clicked() {
+					command = Some(ControlCommand::Start);
+				}
+				if ui.button("Stop recording").clicked() {
+					command = Some(ControlCommand::Stop);
+				}
+				if ui.button("Clear").clicked() {
+					command = Some(ControlCommand::Clear);
+				}
+			});
+			ui.horizontal(|ui| {
+				ui.add(egui::DragValue::new(&mut self.control_frames).clamp_range(1..=1_000_000));
+				if ui.button("Capture frames").clicked() {
+					command = Some(ControlCommand::Capture(self.control_frames));
+				}
+			});
+			ui.horizontal(|ui| {
+				ui.text_edit_singleline(&mut self.control_filter);
+				if ui.button("Filter threads").clicked() {
+					let filter = Some(self.control_filter.trim().to_string()).filter(|filter| !filter.is_empty());
+					command = Some(ControlCommand::Filter(filter));
+				}
+			});
+			ui.horizontal(|ui| {
+				ui.text_edit_singleline(&mut self.control_path);
+				// a file name, saved to the save directory of the profiled process
+				if ui.button("Save remotely").clicked() {
+					command = Some(ControlCommand::Save(self.control_path.clone().into()));
+				}
+			});
+			if let Some(command) = command {
+				let result = match send_command(self.control_address.as_str(), &command) {
+					Ok(()) => "ok".to_string(),
+					Err(e) => e.to_string(),
+				};
+				self.control_result = Some(format!("{}: {}", command, result));
+			}
+			if let Some(control_result) = &self.control_result {
+				ui.label(control_result);
+			}
+		});
+	}
+
+	fn receive_live_frames(&mut self, ctx: &egui::Context) {
+		let Some(live) = &mut self.live else {
+			return;
+		};
+		let mut received = false;
+		loop {
+			match live.messages.try_recv() {
+				Ok(message) => {
+					add_stream_message(&mut live.global_profiler, message);
+					received = true;
+				},
+				Err(TryRecvError::Empty) => break,
+				Err(TryRecvError::Disconnected) => {
+					live.connected = false;
+					break;
+				},
+			}
+		}
+		if received {
+			for thread_profiler in live.global_profiler.thread_profilers.values_mut() {
+				let excess = thread_profiler.frames.len().saturating_sub(LiveConnection::MAX_FRAMES_PER_THREAD);
+				thread_profiler.frames.drain(..excess);
+			}
+			self.profiler = Some(ProcessedGlobalProfiler::new(live.global_profiler.clone()));
+		}
+		if live.connected {
+			ctx.request_repaint();
 		}
 	}
 
@@ -322,6 +1037,16 @@ impl Viewer {
 				}
 			}
 
+			ui.separator();
+			ui.label("... or connect to a running process");
+			ui.horizontal(|ui| {
+				ui.text_edit_singleline(&mut self.connect_address);
+				if ui.button("Connect").clicked() {
+					let address = self.connect_address.clone();
+					self.connect(&address);
+				}
+			});
+
 			if let Some(error) = self.loading_error_msg.clone() {
 				ui.visuals_mut().override_text_color = Some(egui::Color32::RED);
 
@@ -337,6 +1062,62 @@ impl Default for Viewer {
 	}
 }
 
+// diagonal lines across a blocked scope
+fn draw_hatching(painter: &egui::Painter, rect: egui::Rect) {
+	let spacing = 8.0;
+	let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(50));
+	let painter = painter.with_clip_rect(painter.clip_rect().intersect(rect));
+	let mut x = rect.min.x - rect.height();
+	while x < rect.max.x {
+		painter.line_segment([egui::pos2(x, rect.max.y), egui::pos2(x + rect.height(), rect.min.y)], stroke);
+		x += spacing;
+	}
+}
+
+fn event_color(kind: &EventKind) -> egui::Color32 {
+	match kind {
+		EventKind::BudgetViolation { .. } => egui::Color32::RED,
+		EventKind::MessageReceived { .. } => egui::Color32::LIGHT_BLUE,
+		EventKind::Marker => egui::Color32::WHITE,
+		EventKind::Message { level, .. } => match level {
+			Level::Error => egui::Color32::from_rgb(255, 80, 80),
+			Level::Warn => egui::Color32::YELLOW,
+			Level::Info => egui::Color32::GREEN,
+			Level::Debug => egui::Color32::LIGHT_GRAY,
+			Level::Trace => egui::Color32::GRAY,
+		},
+	}
+}
+
+// 0.0..1.0, how much of the wall time the scope actually spent on the cpu
+fn cpu_utilization(scope_result: &profiler::ScopeResult) -> Option<f32> {
+	let resource_usage = scope_result.resource_usage.as_ref()?;
+	if scope_result.duration.is_zero() {
+		return None;
+	}
+	Some((resource_usage.cpu_time.as_secs_f64() / scope_result.duration.as_secs_f64()).min(1.0) as f32)
+}
+
+fn format_bytes(bytes: u64) -> String {
+	const KIB: f64 = 1024.0;
+	const MIB: f64 = KIB * 1024.0;
+	const GIB: f64 = MIB * 1024.0;
+
+	let bytes_f64 = bytes as f64;
+	if bytes_f64 >= GIB {
+		format!("{:.2} GiB", bytes_f64 / GIB)
+	}
+	else if bytes_f64 >= MIB {
+		format!("{:.2} MiB", bytes_f64 / MIB)
+	}
+	else if bytes_f64 >= KIB {
+		format!("{:.2} KiB", bytes_f64 / KIB)
+	}
+	else {
+		format!("{bytes} B")
+	}
+}
+
 fn format_duration(duration: &Duration) -> String {
 	const NANOS_PER_SEC: f32 = 1_000_000_000.0;
 	const NANOS_PER_MILLI: f32 = 1_000_000.0;
@@ -1,7 +1,28 @@
 use eframe::egui;
+use profiler::control::{send_command, ControlCommand, DEFAULT_CONTROL_ADDRESS};
 use profiler_gui::Viewer;
 
+// `--control [--address address] <command>` sends a command to the control endpoint of a running process
+fn control(args: &[String]) -> ! {
+	let (address, command) = match args {
+		[flag, address, command @ ..] if flag == "--address" => (address.as_str(), command),
+		command => (DEFAULT_CONTROL_ADDRESS, command),
+	};
+	let result = command.join(" ").parse::<ControlCommand>()
+		.and_then(|command| send_command(address, &command));
+	if let Err(e) = result {
+		eprintln!("{}", e);
+		std::process::exit(1);
+	}
+	std::process::exit(0);
+}
+
 fn main() -> eframe::Result<()>{
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	if args.first().is_some_and(|arg| arg == "--control") {
+		control(&args[1..]);
+	}
+
 	let options = eframe::NativeOptions {
 		viewport: egui::ViewportBuilder::default().with_inner_size([800.0, 600.0]),
 		..Default::default()
@@ -9,9 +30,15 @@ fn main() -> eframe::Result<()>{
 	
 	let mut viewer = Viewer::new();
 
+	// `--connect [address]` shows the frames streamed by a running process instead of opening a file
+	if args.first().is_some_and(|arg| arg == "--connect") {
+		let address = args.get(1).cloned().unwrap_or_else(|| profiler::server::DEFAULT_ADDRESS.to_string());
+		viewer.connect(&address);
+	}
+
 	eframe::run_simple_native("Profiler GUI", options, move |ctx, _frame| {
 		egui::CentralPanel::default().show(ctx, |ui| {
 			viewer.update(ui);
 		});
 	})
-}
\ No newline at end of file
+}
@@ -0,0 +1,84 @@
+// Counts heap allocations per thread.
+// Install it as the global allocator to get allocations attributed to the innermost open scope:
+// ```
+// #[global_allocator]
+// static ALLOCATOR: profiler::allocation::TrackingAllocator = profiler::allocation::TrackingAllocator::system();
+// ```
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+pub use profiler_core::AllocationStats;
+
+thread_local! {
+	static THREAD_ALLOCATIONS: Cell<AllocationStats> = const { Cell::new(AllocationStats::new()) };
+}
+
+// everything the current thread allocated since it started, always zero without a `TrackingAllocator`
+pub fn thread_allocation_stats() -> AllocationStats {
+	THREAD_ALLOCATIONS.try_with(|stats| stats.get()).unwrap_or_default()
+}
+
+fn record(allocated_bytes: Option<usize>, deallocated_bytes: Option<usize>) {
+	// the thread local may already be destroyed while a thread shuts down
+	let _ = THREAD_ALLOCATIONS.try_with(|stats| {
+		let mut new_stats = stats.get();
+		if let Some(bytes) = allocated_bytes {
+			new_stats.allocations += 1;
+			new_stats.allocated_bytes += bytes as u64;
+		}
+		if let Some(bytes) = deallocated_bytes {
+			new_stats.deallocations += 1;
+			new_stats.deallocated_bytes += bytes as u64;
+		}
+		stats.set(new_stats);
+	});
+}
+
+pub struct TrackingAllocator<A = System> {
+	inner: A,
+}
+
+impl TrackingAllocator<System> {
+	pub const fn system() -> Self {
+		Self::new(System)
+	}
+}
+
+impl<A> TrackingAllocator<A> {
+	pub const fn new(inner: A) -> Self {
+		Self {
+			inner,
+		}
+	}
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let ptr = self.inner.alloc(layout);
+		if !ptr.is_null() {
+			record(Some(layout.size()), None);
+		}
+		ptr
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let ptr = self.inner.alloc_zeroed(layout);
+		if !ptr.is_null() {
+			record(Some(layout.size()), None);
+		}
+		ptr
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		self.inner.dealloc(ptr, layout);
+		record(None, Some(layout.size()));
+	}
+
+	// counted as freeing the old block and allocating the new one
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_ptr = self.inner.realloc(ptr, layout, new_size);
+		if !new_ptr.is_null() {
+			record(Some(new_size), Some(layout.size()));
+		}
+		new_ptr
+	}
+}
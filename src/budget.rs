@@ -0,0 +1,241 @@
+// Time budgets for scopes and whole frames.
+//
+// Budget files contain one budget per line, `#` starts a comment:
+// ```text
+// render::draw <= 4ms
+// frame <= 16.6ms
+// frame physics_tick <= 2ms
+// "jobs (send)" <= 100us
+// frame "fixed update" <= 2ms
+// ```
+// `frame` without a kind refers to frames submitted with `submit_frame!()`.
+// Names with spaces or `#` are quoted, a quoted `"frame"` is the scope of that name rather than the frame kind.
+// Supported units are `ns`, `us`, `ms` and `s`.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::{Result, anyhow, bail};
+use once_cell::sync::Lazy;
+use crate::{DEFAULT_FRAME_KIND, Event, EventKind};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BudgetTarget {
+	Scope(String),
+	// frames of the given kind
+	Frame(String),
+}
+
+impl std::fmt::Display for BudgetTarget {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			BudgetTarget::Scope(name) => write!(f, "{name}"),
+			BudgetTarget::Frame(kind) => write!(f, "frame {kind}"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Budget {
+	pub target: BudgetTarget,
+	pub limit: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetViolation {
+	pub target: BudgetTarget,
+	pub limit: Duration,
+	pub duration: Duration,
+	pub thread_name: String,
+}
+
+impl BudgetViolation {
+	pub fn to_event(&self, time: Duration) -> Event {
+		Event::new(
+			format!("{} over budget", self.target),
+			time,
+			EventKind::BudgetViolation {
+				budget: self.limit,
+				duration: self.duration,
+			},
+		)
+	}
+}
+
+type ViolationCallback = Box<dyn Fn(&BudgetViolation) + Send>;
+
+struct Budgets {
+	scope_limits: HashMap<String, Duration>,
+	frame_limits: HashMap<String, Duration>,
+	violation_counts: HashMap<BudgetTarget, u64>,
+	callback: Option<ViolationCallback>,
+}
+
+static BUDGETS: Lazy<Mutex<Budgets>> = Lazy::new(|| Mutex::new(Budgets {
+	scope_limits: HashMap::new(),
+	frame_limits: HashMap::new(),
+	violation_counts: HashMap::new(),
+	callback: None,
+}));
+// skips locking `BUDGETS` for every scope as long as no budget is set
+static BUDGETS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_budget(budget: Budget) {
+	let mut budgets = BUDGETS.lock().unwrap();
+	match budget.target {
+		BudgetTarget::Scope(name) => budgets.scope_limits.insert(name, budget.limit),
+		BudgetTarget::Frame(kind) => budgets.frame_limits.insert(kind, budget.limit),
+	};
+	BUDGETS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn set_scope_budget<S: Into<String>>(scope_name: S, limit: Duration) {
+	set_budget(Budget { target: BudgetTarget::Scope(scope_name.into()), limit });
+}
+
+pub fn set_frame_budget<S: Into<String>>(frame_kind: S, limit: Duration) {
+	set_budget(Budget { target: BudgetTarget::Frame(frame_kind.into()), limit });
+}
+
+pub fn clear_budgets() {
+	BUDGETS_ENABLED.store(false, Ordering::Relaxed);
+	let mut budgets = BUDGETS.lock().unwrap();
+	budgets.scope_limits.clear();
+	budgets.frame_limits.clear();
+	budgets.violation_counts.clear();
+}
+
+pub fn load_budgets_from_file<P>(path: P) -> Result<()>
+where P: AsRef<Path>
+{
+	for budget in parse_budgets(&std::fs::read_to_string(path)?)? {
+		set_budget(budget);
+	}
+	Ok(())
+}
+
+pub fn parse_budgets(text: &str) -> Result<Vec<Budget>> {
+	let mut budgets = Vec::new();
+	for (i, line) in text.lines().enumerate() {
+		let line = strip_comment(line).trim();
+		if line.is_empty() {
+			continue;
+		}
+		// the duration never contains "<=", a quoted name might
+		let (target, limit) = line.rsplit_once("<=").ok_or_else(|| anyhow!("line {}: expected '<name> <= <duration>'", i + 1))?;
+		let words = split_words(target).map_err(|e| anyhow!("line {}: {e}", i + 1))?;
+		let target = match words.as_slice() {
+			[Word::Bare("frame")] => BudgetTarget::Frame(DEFAULT_FRAME_KIND.to_string()),
+			[Word::Bare("frame"), Word::Bare(kind) | Word::Quoted(kind)] => BudgetTarget::Frame(kind.to_string()),
+			[Word::Bare(name) | Word::Quoted(name)] => BudgetTarget::Scope(name.to_string()),
+			_ => bail!("line {}: invalid budget name '{}'", i + 1, target.trim()),
+		};
+		let limit = parse_duration(limit.trim()).map_err(|e| anyhow!("line {}: {e}", i + 1))?;
+		budgets.push(Budget { target, limit });
+	}
+	Ok(budgets)
+}
+
+// everything in front of a `#` that is not inside quotes
+fn strip_comment(line: &str) -> &str {
+	let mut quoted = false;
+	for (i, c) in line.char_indices() {
+		match c {
+			'"' => quoted = !quoted,
+			'#' if !quoted => return &line[..i],
+			_ => {},
+		}
+	}
+	line
+}
+
+enum Word<'a> {
+	Bare(&'a str),
+	Quoted(&'a str),
+}
+
+fn split_words(text: &str) -> Result<Vec<Word<'_>>> {
+	let mut words = Vec::new();
+	let mut rest = text.trim_start();
+	while !rest.is_empty() {
+		if let Some(quoted) = rest.strip_prefix('"') {
+			let (word, after) = quoted.split_once('"').ok_or_else(|| anyhow!("missing closing quote in '{}'", text.trim()))?;
+			if !after.is_empty() && !after.starts_with(char::is_whitespace) {
+				bail!("expected a space after the quoted name in '{}'", text.trim());
+			}
+			words.push(Word::Quoted(word));
+			rest = after.trim_start();
+		}
+		else {
+			let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+			words.push(Word::Bare(&rest[..end]));
+			rest = rest[end..].trim_start();
+		}
+	}
+	Ok(words)
+}
+
+fn parse_duration(text: &str) -> Result<Duration> {
+	let unit_start = text.find(|c: char| c.is_ascii_alphabetic()).ok_or_else(|| anyhow!("missing unit in '{text}'"))?;
+	let value: f64 = text[..unit_start].trim().parse().map_err(|_| anyhow!("invalid number in '{text}'"))?;
+	if !value.is_finite() || value < 0.0 {
+		bail!("duration '{text}' must not be negative");
+	}
+	let seconds = match &text[unit_start..] {
+		"ns" => value / 1_000_000_000.0,
+		"us" => value / 1_000_000.0,
+		"ms" => value / 1_000.0,
+		"s" => value,
+		unit => bail!("unknown unit '{unit}'"),
+	};
+	Duration::try_from_secs_f64(seconds).map_err(|_| anyhow!("duration '{text}' is out of range"))
+}
+
+// called once per budget violation, while the violating thread is recording and the budgets are locked,
+// so the callback must not change any budgets
+pub fn set_budget_violation_callback<F>(callback: F)
+where F: Fn(&BudgetViolation) + Send + 'static
+{
+	BUDGETS.lock().unwrap().callback = Some(Box::new(callback));
+}
+
+pub fn budget_violation_counts() -> HashMap<BudgetTarget, u64> {
+	BUDGETS.lock().unwrap().violation_counts.clone()
+}
+
+#[cfg(feature = "enable_profiling")]
+pub(crate) fn check_scope_budget(name: &str, duration: Duration, thread_name: &str) -> Option<BudgetViolation> {
+	if !BUDGETS_ENABLED.load(Ordering::Relaxed) {
+		return None;
+	}
+	let mut budgets = BUDGETS.lock().unwrap();
+	let limit = *budgets.scope_limits.get(name)?;
+	violate(&mut budgets, BudgetTarget::Scope(name.to_string()), limit, duration, thread_name)
+}
+
+pub(crate) fn check_frame_budget(kind: &str, duration: Duration, thread_name: &str) -> Option<BudgetViolation> {
+	if !BUDGETS_ENABLED.load(Ordering::Relaxed) {
+		return None;
+	}
+	let mut budgets = BUDGETS.lock().unwrap();
+	let limit = *budgets.frame_limits.get(kind)?;
+	violate(&mut budgets, BudgetTarget::Frame(kind.to_string()), limit, duration, thread_name)
+}
+
+fn violate(budgets: &mut Budgets, target: BudgetTarget, limit: Duration, duration: Duration, thread_name: &str) -> Option<BudgetViolation> {
+	if duration <= limit {
+		return None;
+	}
+	*budgets.violation_counts.entry(target.clone()).or_insert(0) += 1;
+	let violation = BudgetViolation {
+		target,
+		limit,
+		duration,
+		thread_name: thread_name.to_string(),
+	};
+	if let Some(callback) = &budgets.callback {
+		callback(&violation);
+	}
+	Some(violation)
+}
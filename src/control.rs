@@ -0,0 +1,217 @@
+// Controls what is recorded while the program runs, locally or through a control endpoint.
+// The endpoint listens on TCP and takes one command per line, it answers every line with
+// "ok" or "error: <reason>". See `ControlCommand` for the commands.
+// Every frame and scope result passes through here, so the state is kept in atomics,
+// and a thread only reads the thread filter again after it changed.
+use std::cell::Cell;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use anyhow::{Result, anyhow, bail};
+use crate::GLOBAL_PROFILER;
+use crate::sink::ThreadInfo;
+
+// localhost only, next to `server::DEFAULT_ADDRESS`
+pub const DEFAULT_CONTROL_ADDRESS: &str = "127.0.0.1:7374";
+
+static RECORDING: AtomicBool = AtomicBool::new(true);
+// recording stops after this many more frames, unless it is `NO_CAPTURE`
+static REMAINING_FRAMES: AtomicU64 = AtomicU64::new(NO_CAPTURE);
+const NO_CAPTURE: u64 = u64::MAX;
+// only threads whose name contains it are recorded
+static THREAD_FILTER: Mutex<Option<String>> = Mutex::new(None);
+// changes with every new thread filter
+static THREAD_FILTER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+	// the filter generation, thread id and whether that thread passed the filter
+	static THREAD_FILTER_CACHE: Cell<Option<(u64, u64, bool)>> = const { Cell::new(None) };
+}
+
+// frames that are not recorded are dropped before they reach any sink
+pub fn set_recording(recording: bool) {
+	REMAINING_FRAMES.store(NO_CAPTURE, Ordering::Relaxed);
+	RECORDING.store(recording, Ordering::Relaxed);
+}
+
+pub fn is_recording() -> bool {
+	RECORDING.load(Ordering::Relaxed)
+}
+
+// `None` records all threads again
+pub fn set_thread_filter(filter: Option<String>) {
+	*THREAD_FILTER.lock().unwrap() = filter;
+	THREAD_FILTER_GENERATION.fetch_add(1, Ordering::Release);
+}
+
+// starts recording and stops again after `frames` frames of any thread
+pub fn capture_frames(frames: u64) {
+	REMAINING_FRAMES.store(if frames > 0 { frames } else { NO_CAPTURE }, Ordering::Relaxed);
+	RECORDING.store(frames > 0, Ordering::Relaxed);
+}
+
+fn is_thread_recorded(thread: &ThreadInfo) -> bool {
+	let generation = THREAD_FILTER_GENERATION.load(Ordering::Acquire);
+	if let Ok(Some((cached_generation, thread_id, recorded))) = THREAD_FILTER_CACHE.try_with(Cell::get) {
+		if cached_generation == generation && thread_id == thread.id {
+			return recorded;
+		}
+	}
+	let recorded = THREAD_FILTER.lock().unwrap().as_ref().is_none_or(|filter| thread.name.contains(filter.as_str()));
+	let _ = THREAD_FILTER_CACHE.try_with(|cache| cache.set(Some((generation, thread.id, recorded))));
+	recorded
+}
+
+// counts the frame towards a capture
+pub(crate) fn record_frame(thread: &ThreadInfo) -> bool {
+	if !RECORDING.load(Ordering::Relaxed) || !is_thread_recorded(thread) {
+		return false;
+	}
+	let remaining_frames = REMAINING_FRAMES.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining_frames| match remaining_frames {
+		NO_CAPTURE => None,
+		remaining_frames => remaining_frames.checked_sub(1),
+	});
+	match remaining_frames {
+		// the last frame of the capture
+		Ok(1) => {
+			RECORDING.store(false, Ordering::Relaxed);
+			true
+		},
+		Ok(_) => true,
+		// a capture that other threads already completed
+		Err(remaining_frames) => remaining_frames == NO_CAPTURE,
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+pub(crate) fn record_scope_result(thread: &ThreadInfo) -> bool {
+	RECORDING.load(Ordering::Relaxed) && is_thread_recorded(thread)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ControlCommand {
+	// "start"
+	Start,
+	// "stop"
+	Stop,
+	// "filter <part of a thread name>", "filter" alone records all threads
+	Filter(Option<String>),
+	// "capture <frames>"
+	Capture(u64),
+	// "save <path>", relative paths are relative to the working directory of the profiled program.
+	// The control endpoint only takes a file name and saves it to its save directory, see `start_control_server`.
+	Save(PathBuf),
+	// "clear"
+	Clear,
+}
+
+impl ControlCommand {
+	pub fn execute(&self) -> Result<()> {
+		match self {
+			ControlCommand::Start => set_recording(true),
+			ControlCommand::Stop => set_recording(false),
+			ControlCommand::Filter(filter) => set_thread_filter(filter.clone()),
+			ControlCommand::Capture(frames) => capture_frames(*frames),
+			ControlCommand::Save(path) => GLOBAL_PROFILER.lock().unwrap().save_to_file(path)?,
+			ControlCommand::Clear => crate::reset(),
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for ControlCommand {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ControlCommand::Start => write!(f, "start"),
+			ControlCommand::Stop => write!(f, "stop"),
+			ControlCommand::Filter(None) => write!(f, "filter"),
+			ControlCommand::Filter(Some(filter)) => write!(f, "filter {}", filter),
+			ControlCommand::Capture(frames) => write!(f, "capture {}", frames),
+			ControlCommand::Save(path) => write!(f, "save {}", path.display()),
+			ControlCommand::Clear => write!(f, "clear"),
+		}
+	}
+}
+
+impl FromStr for ControlCommand {
+	type Err = anyhow::Error;
+
+	fn from_str(line: &str) -> Result<Self> {
+		let line = line.trim();
+		let (name, argument) = line.split_once(' ').map_or((line, ""), |(name, argument)| (name, argument.trim()));
+		let command = match name {
+			"start" => ControlCommand::Start,
+			"stop" => ControlCommand::Stop,
+			"filter" => ControlCommand::Filter(Some(argument.to_string()).filter(|filter| !filter.is_empty())),
+			"capture" => ControlCommand::Capture(argument.parse().map_err(|_| anyhow!("capture needs a number of frames"))?),
+			"save" if !argument.is_empty() => ControlCommand::Save(PathBuf::from(argument)),
+			"save" => bail!("save needs a path"),
+			"clear" => ControlCommand::Clear,
+			_ => bail!("unknown command {:?}", name),
+		};
+		Ok(command)
+	}
+}
+
+/// Listens for control commands on `address`, returns the address it listens on.
+/// Anyone who can connect can send commands, `save` only writes files into `save_dir`.
+pub fn start_control_server<A: ToSocketAddrs, P: Into<PathBuf>>(address: A, save_dir: P) -> Result<SocketAddr> {
+	let listener = TcpListener::bind(address)?;
+	let address = listener.local_addr()?;
+	let save_dir = save_dir.into();
+	std::thread::Builder::new().name("profiler control".to_string()).spawn(move || {
+		for client in listener.incoming().flatten() {
+			// a client that stays connected doesn't keep the others waiting
+			let save_dir = save_dir.clone();
+			let _ = std::thread::Builder::new().name("profiler control client".to_string()).spawn(move || {
+				let _ = handle_client(client, &save_dir);
+			});
+		}
+	})?;
+	Ok(address)
+}
+
+// clients name the file, they can't write anywhere outside of `save_dir`
+fn save_path(save_dir: &Path, path: &Path) -> Result<PathBuf> {
+	let mut components = path.components();
+	match (components.next(), components.next()) {
+		(Some(Component::Normal(name)), None) => Ok(save_dir.join(name)),
+		_ => bail!("save takes a file name, the file is written to the save directory of the profiled program"),
+	}
+}
+
+fn execute_remote(command: ControlCommand, save_dir: &Path) -> Result<()> {
+	match command {
+		ControlCommand::Save(path) => ControlCommand::Save(save_path(save_dir, &path)?).execute(),
+		command => command.execute(),
+	}
+}
+
+fn handle_client(client: TcpStream, save_dir: &Path) -> Result<()> {
+	let mut writer = client.try_clone()?;
+	for line in BufReader::new(client).lines() {
+		let response = match line?.parse::<ControlCommand>().and_then(|command| execute_remote(command, save_dir)) {
+			Ok(()) => "ok".to_string(),
+			Err(e) => format!("error: {}", e),
+		};
+		writeln!(writer, "{}", response)?;
+	}
+	Ok(())
+}
+
+/// Sends `command` to the control endpoint at `address` and waits until it was executed
+pub fn send_command<A: ToSocketAddrs>(address: A, command: &ControlCommand) -> Result<()> {
+	let mut stream = TcpStream::connect(address)?;
+	writeln!(stream, "{}", command)?;
+	let mut response = String::new();
+	BufReader::new(stream).read_line(&mut response)?;
+	match response.trim_end() {
+		"ok" => Ok(()),
+		"" => bail!("the control endpoint closed the connection"),
+		response => Err(anyhow!("{}", response.strip_prefix("error: ").unwrap_or(response))),
+	}
+}
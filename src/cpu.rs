@@ -0,0 +1,16 @@
+// the cpu core the calling thread is running on right now, only implemented on linux
+#[cfg(target_os = "linux")]
+pub fn current_cpu() -> Option<u32> {
+	let cpu = unsafe { libc::sched_getcpu() };
+	if cpu < 0 {
+		None
+	}
+	else {
+		Some(cpu as u32)
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current_cpu() -> Option<u32> {
+	None
+}
@@ -0,0 +1,122 @@
+// Instrumentation for futures: every poll is recorded as a scope on the thread that ran it,
+// and all polls of one future are linked into a `TaskSpan` by its task id.
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+#[cfg(feature = "enable_profiling")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "enable_profiling")]
+use crate::{Scope, PROFILER};
+pub use profiler_core::TaskSpan;
+
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_task_id() -> u64 {
+	NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+pub trait InstrumentFuture: Future + Sized {
+	fn instrumented<S: Into<String>>(self, name: S) -> Instrumented<Self> {
+		Instrumented::new(name, self)
+	}
+}
+
+impl<F: Future> InstrumentFuture for F {}
+
+pub struct Instrumented<F> {
+	name: String,
+	task_id: u64,
+	#[cfg(feature = "enable_profiling")]
+	first_poll: Option<Instant>,
+	#[cfg(feature = "enable_profiling")]
+	polls: u64,
+	#[cfg(feature = "enable_profiling")]
+	busy: Duration,
+	#[cfg(feature = "enable_profiling")]
+	completed: bool,
+	future: F,
+}
+
+impl<F: Future> Instrumented<F> {
+	pub fn new<S: Into<String>>(name: S, future: F) -> Self {
+		Self {
+			name: name.into(),
+			task_id: next_task_id(),
+			#[cfg(feature = "enable_profiling")]
+			first_poll: None,
+			#[cfg(feature = "enable_profiling")]
+			polls: 0,
+			#[cfg(feature = "enable_profiling")]
+			busy: Duration::ZERO,
+			#[cfg(feature = "enable_profiling")]
+			completed: false,
+			future,
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn task_id(&self) -> u64 {
+		self.task_id
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+impl<F> Instrumented<F> {
+	fn submit_task_span(&self) {
+		let Some(first_poll) = self.first_poll else {
+			return;
+		};
+		let task_span = TaskSpan {
+			id: self.task_id,
+			name: self.name.clone(),
+			start: Duration::ZERO,
+			duration: first_poll.elapsed(),
+			polls: self.polls,
+			busy: self.busy,
+			completed: self.completed,
+		};
+		PROFILER.with_borrow_mut(|p| p.submit_task_span(task_span, first_poll));
+	}
+}
+
+impl<F: Future> Future for Instrumented<F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+		// SAFETY: `future` is never moved out of `self`, the other fields are not structurally pinned
+		let this = unsafe { self.get_unchecked_mut() };
+		let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+		#[cfg(feature = "enable_profiling")]
+		{
+			let start = Instant::now();
+			this.first_poll.get_or_insert(start);
+			this.polls += 1;
+			let scope = Scope::new(this.name.clone());
+			scope.set_task(this.task_id);
+			let poll = future.poll(cx);
+			drop(scope);
+			this.busy += start.elapsed();
+			if poll.is_ready() {
+				this.completed = true;
+				this.submit_task_span();
+			}
+			poll
+		}
+		#[cfg(not(feature = "enable_profiling"))]
+		future.poll(cx)
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+impl<F> Drop for Instrumented<F> {
+	fn drop(&mut self) {
+		if !self.completed {
+			self.submit_task_span();
+		}
+	}
+}
@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+pub use profiler_core::GlobalFrame;
+
+static GLOBAL_FRAMES_ENABLED: AtomicBool = AtomicBool::new(false);
+static CURRENT_GLOBAL_FRAME: AtomicU64 = AtomicU64::new(0);
+
+// the global frame id that work started now belongs to, `None` if no thread defines global frames
+pub fn current_global_frame() -> Option<u64> {
+	if GLOBAL_FRAMES_ENABLED.load(Ordering::Relaxed) {
+		Some(CURRENT_GLOBAL_FRAME.load(Ordering::Relaxed))
+	}
+	else {
+		None
+	}
+}
+
+pub(crate) fn enable_global_frames() {
+	GLOBAL_FRAMES_ENABLED.store(true, Ordering::Relaxed);
+}
+
+// returns the id of the global frame that just ended
+pub(crate) fn advance_global_frame() -> u64 {
+	CURRENT_GLOBAL_FRAME.fetch_add(1, Ordering::Relaxed)
+}
@@ -0,0 +1,126 @@
+// `Read`/`Write` wrappers that record every read, write and flush as a scope with the
+// transferred bytes, and keep per-file totals in every frame, see `GlobalProfiler::io_statistics`.
+use std::fs::File;
+use std::io::{self, Read, Write, Seek, SeekFrom};
+use std::path::Path;
+#[cfg(feature = "enable_profiling")]
+use std::time::Instant;
+#[cfg(feature = "enable_profiling")]
+use crate::{Scope, ScopeKind, PROFILER};
+pub use profiler_core::{IoStatistics, throughput};
+
+// opens `path` for reading, named after the path
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Profiled<File>> {
+	let path = path.as_ref();
+	Ok(Profiled::new(path.display().to_string(), File::open(path)?))
+}
+
+// creates or truncates `path` for writing, named after the path
+pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Profiled<File>> {
+	let path = path.as_ref();
+	Ok(Profiled::new(path.display().to_string(), File::create(path)?))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+	Read,
+	Write,
+	Flush,
+}
+
+pub struct Profiled<T> {
+	name: String,
+	inner: T,
+}
+
+impl<T> Profiled<T> {
+	pub fn new<S: Into<String>>(name: S, inner: T) -> Self {
+		Self {
+			name: name.into(),
+			inner,
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.inner
+	}
+
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn record(&mut self, operation: Operation, f: impl FnOnce(&mut T) -> io::Result<usize>) -> io::Result<usize> {
+		let scope_name = match operation {
+			Operation::Read => format!("{} (read)", self.name),
+			Operation::Write => format!("{} (write)", self.name),
+			Operation::Flush => format!("{} (flush)", self.name),
+		};
+		let scope = Scope::with_kind(scope_name, ScopeKind::Io);
+		let start = Instant::now();
+		let result = f(&mut self.inner);
+		let duration = start.elapsed();
+		let bytes = *result.as_ref().unwrap_or(&0) as u64;
+		if !matches!(operation, Operation::Flush) {
+			scope.set_bytes(bytes);
+		}
+		drop(scope);
+
+		PROFILER.with_borrow_mut(|p| {
+			let statistics = p.io_statistics(&self.name);
+			match operation {
+				Operation::Read => {
+					statistics.reads += 1;
+					statistics.bytes_read += bytes;
+					statistics.read_time += duration;
+				},
+				Operation::Write => {
+					statistics.writes += 1;
+					statistics.bytes_written += bytes;
+					statistics.write_time += duration;
+				},
+				Operation::Flush => {
+					statistics.flushes += 1;
+					statistics.flush_time += duration;
+				},
+			}
+		});
+		result
+	}
+
+	#[cfg(not(feature = "enable_profiling"))]
+	fn record(&mut self, _operation: Operation, f: impl FnOnce(&mut T) -> io::Result<usize>) -> io::Result<usize> {
+		f(&mut self.inner)
+	}
+}
+
+impl<R: Read> Read for Profiled<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.record(Operation::Read, |inner| inner.read(buf))
+	}
+}
+
+impl<W: Write> Write for Profiled<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.record(Operation::Write, |inner| inner.write(buf))
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.record(Operation::Flush, |inner| inner.flush().map(|_| 0)).map(|_| ())
+	}
+}
+
+// seeking is not recorded
+impl<S: Seek> Seek for Profiled<S> {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		self.inner.seek(pos)
+	}
+}
@@ -1,36 +1,77 @@
-use std::{hash::{Hash, Hasher}, time::{Duration, Instant}};
+use std::{hash::{Hash, Hasher}, time::Instant};
+#[cfg(feature = "enable_profiling")]
+use std::time::Duration;
 #[cfg(feature = "enable_profiling")]
 use std::cell::RefCell;
 use std::sync::Mutex;
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
-use serde::{Serialize, Deserialize};
 
 mod function_name;
 mod serialization;
+pub use profiler_core::{Frame, ThreadProfiler, GlobalProfiler, DEFAULT_FRAME_KIND};
+pub use profiler_core::{save_sessions_to_file, load_sessions_from_file, sessions_to_binary, sessions_from_binary};
 mod scope;
+mod session;
+pub use session::{start_session, end_session, reset};
+pub use profiler_core::FrameStatistics;
+mod global_frame;
+pub use global_frame::{GlobalFrame, current_global_frame};
+pub mod sink;
+pub use sink::{Sink, SinkId, ThreadInfo, MemorySink, FileStreamSink, CallbackSink, add_sink, remove_sink};
+pub mod stream;
+pub mod server;
+pub mod control;
+pub use profiler_core::{Event, EventKind, Level};
+pub mod budget;
+pub mod allocation;
+use allocation::{AllocationStats, thread_allocation_stats};
+pub mod resource_usage;
+#[cfg(feature = "enable_profiling")]
+use resource_usage::ResourceUsage;
+pub mod perf_counters;
+mod cpu;
+pub use cpu::current_cpu;
+pub use profiler_core::Sample;
+#[cfg(all(feature = "sampling", target_os = "linux"))]
+pub mod sampling;
+pub mod sync;
+pub mod io;
+#[cfg(feature = "enable_profiling")]
+use io::IoStatistics;
+pub mod future;
+pub use future::{Instrumented, InstrumentFuture, TaskSpan};
+pub mod process;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(feature = "log")]
+pub mod log;
+#[cfg(feature = "rayon")]
+pub mod rayon;
+#[cfg(feature = "enable_profiling")]
+use sync::{LockStatistics, mpsc::ChannelStatistics};
+#[cfg(feature = "enable_profiling")]
+use perf_counters::PerfCounters;
 #[cfg(feature = "enable_profiling")]
 pub use scope::Scope;
-pub use scope::ScopeResult;
+pub use scope::{ScopeResult, ScopeKind, ScopeParent, sleep};
 
 #[cfg(feature = "attribute")]
 pub use profiler_attributes::profile;
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-pub struct Frame {
-	pub start: Duration,
-	pub duration: Duration,
-	pub scope_results: Vec<ScopeResult>,
-}
+// an auto submitting thread submits its frame once the outermost scope ends after this long or this many scopes
+#[cfg(feature = "enable_profiling")]
+const AUTO_SUBMIT_INTERVAL: Duration = Duration::from_millis(10);
+#[cfg(feature = "enable_profiling")]
+const AUTO_SUBMIT_MAX_SCOPES: usize = 1024;
 
-impl Frame {
-	fn new(program_start: &Instant) -> Self {
-		Self {
-			start: Instant::now().duration_since(*program_start),
-			duration: Duration::from_secs(0),
-			scope_results: Vec::new(),
-		}
-	}
+// a new frame on the time base of `program_start`, in the current global frame
+fn new_frame(program_start: &Instant) -> Frame {
+	let mut frame = Frame::new(Instant::now().duration_since(*program_start));
+	frame.global_frame = current_global_frame();
+	frame
 }
 
 
@@ -39,44 +80,285 @@ thread_local! {
 	pub static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
 }
 
+// shared time base of all threads
+pub static PROGRAM_START: Lazy<Instant> = Lazy::new(Instant::now);
+
+#[cfg(feature = "enable_profiling")]
+struct OpenScope {
+	// scopes can end out of order, e.g. lock guards, so they are found by id instead of their position
+	id: u64,
+	depth: usize,
+	allocations_start: AllocationStats,
+	child_allocations: AllocationStats,
+	resource_usage_start: Option<ResourceUsage>,
+	perf_counters_start: Option<PerfCounters>,
+	cpu_start: Option<u32>,
+	bytes: Option<u64>,
+	task: Option<u64>,
+	metadata: Vec<(String, String)>,
+}
+
 pub struct Profiler {
 	current_frame: Frame,
+	current_frame_allocations_start: AllocationStats,
+	#[cfg(feature = "enable_profiling")]
+	open_scopes: Vec<OpenScope>,
 	#[cfg(feature = "enable_profiling")]
-	current_frame_call_depth: usize,
+	next_scope_id: u64,
+	next_frame_ids: HashMap<String, u64>,
+	// frames of this kind define the global frame boundaries
+	global_frame_kind: Option<String>,
+	thread: ThreadInfo,
 	program_start: Instant,
+	// submits a frame of this kind when the outermost scope ends, see `AUTO_SUBMIT_INTERVAL`
+	auto_submit_frame_kind: Option<String>,
+	// the scope on another thread that started the work this thread is doing
+	inherited_parent: Option<ScopeParent>,
+	// the session generation the current frame started in, see `session`
+	session: u64,
 }
 
 impl Profiler {
 	pub fn new() -> Self {
-		let program_start = Instant::now();
+		let program_start = *PROGRAM_START;
 		Self {
-			current_frame: Frame::new(&program_start),
+			current_frame: new_frame(&program_start),
+			current_frame_allocations_start: thread_allocation_stats(),
 			#[cfg(feature = "enable_profiling")]
-			current_frame_call_depth: 0,
+			open_scopes: Vec::new(),
+			#[cfg(feature = "enable_profiling")]
+			next_scope_id: 0,
+			next_frame_ids: HashMap::new(),
+			global_frame_kind: None,
+			thread: ThreadInfo::current(),
 			program_start,
+			auto_submit_frame_kind: None,
+			inherited_parent: None,
+			session: session::generation(),
 		}
 	}
 
 	pub fn submit_frame(&mut self) {
+		self.submit_frame_of_kind(DEFAULT_FRAME_KIND);
+	}
+
+	// ends the current frame as a frame of `kind`, every kind has its own sequence of frame ids.
+	// The frames of a thread follow each other whatever their kind, a frame starts where the previous one of any kind ended,
+	// so kinds must not overlap on one thread, e.g. physics ticks inside a render frame end the render frame early.
+	pub fn submit_frame_of_kind(&mut self, kind: &str) {
+		let next_frame_id = self.next_frame_ids.entry(kind.to_string()).or_insert(0);
+		self.current_frame.id = *next_frame_id;
+		*next_frame_id += 1;
+		self.current_frame.kind = kind.to_string();
 		self.current_frame.duration = std::time::Instant::now().duration_since(self.program_start) - self.current_frame.start;
-		let thread_id = get_current_thread_id_u64();
-		let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
-		global_profiler.thread_profilers
-			.entry(thread_id)
-			.or_default()
-			.frames.push(self.current_frame.clone());
-		self.current_frame = Frame::new(&self.program_start);
+		if let Some(violation) = budget::check_frame_budget(kind, self.current_frame.duration, &self.thread.name) {
+			let end = self.current_frame.start + self.current_frame.duration;
+			self.current_frame.events.push(violation.to_event(end));
+		}
+		if self.global_frame_kind.as_deref() == Some(kind) {
+			self.current_frame.global_frame = Some(global_frame::advance_global_frame());
+		}
+		let allocations = thread_allocation_stats();
+		self.current_frame.allocations = allocations - self.current_frame_allocations_start;
+		self.current_frame_allocations_start = allocations;
+		#[cfg(all(feature = "sampling", target_os = "linux"))]
+		{
+			self.current_frame.samples = sampling::take_samples();
+		}
+		let frame = std::mem::replace(&mut self.current_frame, new_frame(&self.program_start));
+		// a frame that started before a session was started, ended or reset belongs to neither session
+		let session = session::generation();
+		if std::mem::replace(&mut self.session, session) == session {
+			sink::submit_frame(&self.thread, &frame);
+		}
+	}
+
+	// drops the frame in progress, for the thread that starts or ends a session
+	#[cfg(feature = "enable_profiling")]
+	fn restart_frame(&mut self) {
+		self.current_frame = new_frame(&self.program_start);
+		self.current_frame_allocations_start = thread_allocation_stats();
+		self.session = session::generation();
 	}
 
+	// the next frame of `kind` gets `id` and the ones after it count on from there, for frame numbers of an engine
+	pub fn set_next_frame_id(&mut self, kind: &str, id: u64) {
+		self.next_frame_ids.insert(kind.to_string(), id);
+	}
+
+	// makes every frame of `kind` submitted by this thread end the current global frame
+	pub fn define_global_frames(&mut self, kind: &str) {
+		self.global_frame_kind = Some(kind.to_string());
+		self.thread.defines_global_frames = true;
+		global_frame::enable_global_frames();
+		self.current_frame.global_frame = current_global_frame();
+	}
+
+	// names the lane of this thread instead of its thread name, before the thread submits its first frame
+	pub fn set_thread_name(&mut self, name: String) {
+		self.thread.name = name;
+	}
+
+	// for threads that never call `submit_frame!` themselves, e.g. thread pool workers
+	pub fn auto_submit_frames(&mut self, kind: Option<&str>) {
+		self.auto_submit_frame_kind = kind.map(str::to_string);
+	}
+
+	// submits the scopes an auto submitting thread collected since its last frame, unless a scope is still open
 	#[cfg(feature = "enable_profiling")]
-	fn begin_profile_result(&mut self) {
-		self.current_frame_call_depth += 1;
+	pub fn flush_auto_submit_frame(&mut self) {
+		if !self.open_scopes.is_empty() || self.current_frame.scope_results.is_empty() {
+			return;
+		}
+		if let Some(kind) = self.auto_submit_frame_kind.clone() {
+			self.submit_frame_of_kind(&kind);
+		}
+	}
+
+	// the innermost open scope of this thread, for work that is handed to another thread
+	#[cfg(feature = "enable_profiling")]
+	pub fn current_parent(&self) -> Option<ScopeParent> {
+		Some(ScopeParent {
+			thread: self.thread.id,
+			depth: self.open_scopes.last()?.depth,
+		})
+	}
+
+	// top level scopes of this thread are attributed to `parent` until it is replaced, returns the previous parent
+	pub fn set_inherited_parent(&mut self, parent: Option<ScopeParent>) -> Option<ScopeParent> {
+		std::mem::replace(&mut self.inherited_parent, parent)
+	}
+
+	pub fn set_frame_label(&mut self, label: String) {
+		self.current_frame.label = Some(label);
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn lock_statistics(&mut self, name: &str) -> &mut LockStatistics {
+		if !self.current_frame.locks.contains_key(name) {
+			self.current_frame.locks.insert(name.to_string(), LockStatistics::default());
+		}
+		self.current_frame.locks.get_mut(name).unwrap()
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn channel_statistics(&mut self, name: &str) -> &mut ChannelStatistics {
+		if !self.current_frame.channels.contains_key(name) {
+			self.current_frame.channels.insert(name.to_string(), ChannelStatistics::default());
+		}
+		self.current_frame.channels.get_mut(name).unwrap()
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn io_statistics(&mut self, name: &str) -> &mut IoStatistics {
+		if !self.current_frame.files.contains_key(name) {
+			self.current_frame.files.insert(name.to_string(), IoStatistics::default());
+		}
+		self.current_frame.files.get_mut(name).unwrap()
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn open_scope_mut(&mut self, id: u64) -> Option<&mut OpenScope> {
+		self.open_scopes.iter_mut().rfind(|open_scope| open_scope.id == id)
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn set_scope_bytes(&mut self, id: u64, bytes: u64) {
+		if let Some(open_scope) = self.open_scope_mut(id) {
+			open_scope.bytes = Some(bytes);
+		}
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn set_scope_task(&mut self, id: u64, task: u64) {
+		if let Some(open_scope) = self.open_scope_mut(id) {
+			open_scope.task = Some(task);
+		}
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn add_scope_metadata(&mut self, id: u64, key: String, value: String) {
+		if let Some(open_scope) = self.open_scope_mut(id) {
+			open_scope.metadata.push((key, value));
+		}
 	}
 
 	#[cfg(feature = "enable_profiling")]
-	fn submit_profile_result(&mut self, name: String, start: Instant, duration: Duration) {
-		self.current_frame.scope_results.push(ScopeResult::new(name, start.duration_since(self.program_start), duration, self.current_frame_call_depth - 1));
-		self.current_frame_call_depth -= 1;
+	fn submit_task_span(&mut self, mut task_span: TaskSpan, first_poll: Instant) {
+		task_span.start = first_poll.duration_since(self.program_start);
+		self.current_frame.tasks.push(task_span);
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	pub fn push_event(&mut self, name: String, kind: EventKind) {
+		let time = Instant::now().duration_since(self.program_start);
+		self.current_frame.events.push(Event::new(name, time, kind));
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn begin_profile_result(&mut self) -> u64 {
+		let id = self.next_scope_id;
+		self.next_scope_id += 1;
+		let depth = self.open_scopes.last().map_or(0, |open_scope| open_scope.depth + 1);
+		self.open_scopes.push(OpenScope {
+			id,
+			depth,
+			allocations_start: thread_allocation_stats(),
+			child_allocations: AllocationStats::new(),
+			resource_usage_start: resource_usage::sample(),
+			perf_counters_start: perf_counters::sample(),
+			cpu_start: current_cpu(),
+			bytes: None,
+			task: None,
+			metadata: Vec::new(),
+		});
+		id
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn submit_profile_result(&mut self, id: u64, name: String, start: Instant, duration: Duration, global_frame: Option<u64>, kind: ScopeKind) {
+		// a scope that was moved to another thread has no open scope here
+		let Some(index) = self.open_scopes.iter().rposition(|open_scope| open_scope.id == id) else {
+			return;
+		};
+		let open_scope = self.open_scopes.remove(index);
+		let mut scope_result = ScopeResult::new(name, start.duration_since(self.program_start), duration, open_scope.depth);
+		scope_result.global_frame = global_frame;
+		scope_result.kind = kind;
+		scope_result.cpu_start = open_scope.cpu_start;
+		scope_result.cpu_end = current_cpu();
+		scope_result.bytes = open_scope.bytes;
+		scope_result.task = open_scope.task;
+		scope_result.metadata = open_scope.metadata;
+		if open_scope.depth == 0 {
+			scope_result.parent = self.inherited_parent;
+		}
+		// allocations are attributed to the innermost open scope only
+		let allocations = thread_allocation_stats() - open_scope.allocations_start;
+		scope_result.allocations = allocations - open_scope.child_allocations;
+		if let Some(parent) = index.checked_sub(1).and_then(|index| self.open_scopes.get_mut(index)) {
+			parent.child_allocations = parent.child_allocations + allocations;
+		}
+		if let Some(resource_usage_start) = open_scope.resource_usage_start {
+			scope_result.resource_usage = resource_usage::sample().map(|resource_usage| resource_usage - resource_usage_start);
+		}
+		if let Some(perf_counters_start) = open_scope.perf_counters_start {
+			scope_result.perf_counters = perf_counters::sample().map(|perf_counters| perf_counters - perf_counters_start);
+		}
+		if let Some(violation) = budget::check_scope_budget(&scope_result.name, duration, &self.thread.name) {
+			let end = scope_result.start + scope_result.duration;
+			self.current_frame.events.push(violation.to_event(end));
+		}
+		sink::submit_scope_result(&self.thread, &scope_result);
+		self.current_frame.scope_results.push(scope_result);
+		// short jobs are batched, a frame per job would cost more than the job
+		let frame_age = Instant::now().duration_since(self.program_start).saturating_sub(self.current_frame.start);
+		let frame_full = frame_age >= AUTO_SUBMIT_INTERVAL || self.current_frame.scope_results.len() >= AUTO_SUBMIT_MAX_SCOPES;
+		if self.open_scopes.is_empty() && frame_full {
+			if let Some(kind) = self.auto_submit_frame_kind.clone() {
+				self.submit_frame_of_kind(&kind);
+			}
+		}
 	}
 }
 
@@ -95,6 +377,11 @@ macro_rules! submit_frame {
 			profiler::PROFILER.with_borrow_mut(|p| p.submit_frame());
 		}
 	};
+	($kind:expr) => {
+		{
+			profiler::PROFILER.with_borrow_mut(|p| p.submit_frame_of_kind($kind));
+		}
+	};
 }
 
 #[macro_export]
@@ -102,61 +389,71 @@ macro_rules! submit_frame {
 macro_rules! submit_frame {
 	() => {
 		
+	};
+	($kind:expr) => {
+		
 	};
 }
 
-
-fn get_current_thread_id_u64() -> u64 {
-	let thread_id = std::thread::current().id();
-	let mut hasher = std::hash::DefaultHasher::default();
-	thread_id.hash(&mut hasher);
-	hasher.finish()
+#[macro_export]
+#[cfg(feature = "enable_profiling")]
+macro_rules! define_global_frames {
+	() => {
+		{
+			profiler::PROFILER.with_borrow_mut(|p| p.define_global_frames(profiler::DEFAULT_FRAME_KIND));
+		}
+	};
+	($kind:expr) => {
+		{
+			profiler::PROFILER.with_borrow_mut(|p| p.define_global_frames($kind));
+		}
+	};
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ThreadProfiler {
-	pub name: String,
-	pub frames: Vec<Frame>,
+#[macro_export]
+#[cfg(not(feature = "enable_profiling"))]
+macro_rules! define_global_frames {
+	() => {
+		
+	};
+	($kind:expr) => {
+		
+	};
 }
 
-impl ThreadProfiler {
-	pub fn new(name: String, frames: Vec<Frame>) -> Self {
-		Self {
-			name,
-			frames,
+#[macro_export]
+#[cfg(feature = "enable_profiling")]
+macro_rules! frame_label {
+	($label:expr) => {
+		{
+			profiler::PROFILER.with_borrow_mut(|p| p.set_frame_label($label.to_string()));
 		}
-	}
+	};
 }
 
-impl Default for ThreadProfiler {
-	fn default() -> Self {
-		let thread_name = if let Some(thread_name) = std::thread::current().name() {
-			thread_name.to_string()
-		}
-		else {
-			"Unnamed Thread".to_string()
-		};
-		Self::new(thread_name, Vec::new())
-	}
+#[macro_export]
+#[cfg(not(feature = "enable_profiling"))]
+macro_rules! frame_label {
+	($label:expr) => {
+		
+	};
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct GlobalProfiler {
-	pub thread_profilers: HashMap<u64, ThreadProfiler>,
-}
 
-impl GlobalProfiler {
-	pub fn new() -> Self {
-		Self {
-			thread_profilers: HashMap::new(),
-		}
+fn get_current_thread_name() -> String {
+	if let Some(thread_name) = std::thread::current().name() {
+		thread_name.to_string()
+	}
+	else {
+		"Unnamed Thread".to_string()
 	}
 }
 
-impl Default for GlobalProfiler {
-	fn default() -> Self {
-		Self::new()
-	}
+fn get_current_thread_id_u64() -> u64 {
+	let thread_id = std::thread::current().id();
+	let mut hasher = std::hash::DefaultHasher::default();
+	thread_id.hash(&mut hasher);
+	hasher.finish()
 }
 
 pub static GLOBAL_PROFILER: Lazy<Mutex<GlobalProfiler>> = Lazy::new(|| Mutex::new(GlobalProfiler::new()));
\ No newline at end of file
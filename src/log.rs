@@ -0,0 +1,78 @@
+// A `log::Log` wrapper that forwards every record to the existing logger and also records it
+// as an `EventKind::Message` event in the current thread's frame.
+use log::{Log, Metadata, Record, LevelFilter, SetLoggerError};
+use crate::Level;
+#[cfg(feature = "enable_profiling")]
+use crate::{EventKind, PROFILER};
+
+pub fn level(level: log::Level) -> Level {
+	match level {
+		log::Level::Error => Level::Error,
+		log::Level::Warn => Level::Warn,
+		log::Level::Info => Level::Info,
+		log::Level::Debug => Level::Debug,
+		log::Level::Trace => Level::Trace,
+	}
+}
+
+pub struct ProfilerLogger<L> {
+	inner: L,
+}
+
+impl<L: Log> ProfilerLogger<L> {
+	pub fn new(inner: L) -> Self {
+		Self {
+			inner,
+		}
+	}
+
+	pub fn inner(&self) -> &L {
+		&self.inner
+	}
+}
+
+impl<L: Log + 'static> ProfilerLogger<L> {
+	// installs the logger globally, records above `max_level` are neither logged nor recorded
+	pub fn init(self, max_level: LevelFilter) -> Result<(), SetLoggerError> {
+		log::set_boxed_logger(Box::new(self))?;
+		log::set_max_level(max_level);
+		Ok(())
+	}
+}
+
+impl<L: Log> Log for ProfilerLogger<L> {
+	// records are recorded even if the inner logger filters them out
+	fn enabled(&self, _metadata: &Metadata) -> bool {
+		true
+	}
+
+	fn log(&self, record: &Record) {
+		if self.inner.enabled(record.metadata()) {
+			self.inner.log(record);
+		}
+
+		#[cfg(feature = "enable_profiling")]
+		{
+			let mut fields = Vec::new();
+			if let (Some(file), Some(line)) = (record.file(), record.line()) {
+				fields.push(("location".to_string(), format!("{file}:{line}")));
+			}
+			let kind = EventKind::Message {
+				level: level(record.level()),
+				target: record.target().to_string(),
+				fields,
+			};
+			let name = record.args().to_string();
+			// logging from inside the profiler, e.g. a sink, or during thread teardown drops the record instead of panicking
+			let _ = PROFILER.try_with(|p| {
+				if let Ok(mut p) = p.try_borrow_mut() {
+					p.push_event(name, kind);
+				}
+			});
+		}
+	}
+
+	fn flush(&self) {
+		self.inner.flush();
+	}
+}
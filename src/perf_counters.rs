@@ -0,0 +1,161 @@
+// Per-thread hardware counters through perf_event_open, attached to every scope when enabled.
+// Falls back to software counters when the hardware ones can't be opened (e.g. inside most VMs)
+// and to no counters at all when perf events are unavailable. Only implemented on linux.
+use std::sync::atomic::{AtomicBool, Ordering};
+pub use profiler_core::{PerfCounter, PerfCounters};
+
+static PERF_COUNTERS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+// the counters of a thread are opened the first time that thread samples them
+pub fn enable_perf_counters(enabled: bool) {
+	PERF_COUNTERS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_perf_counters_enabled() -> bool {
+	PERF_COUNTERS_ENABLED.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "enable_profiling")]
+pub(crate) fn sample() -> Option<PerfCounters> {
+	if is_perf_counters_enabled() {
+		thread_perf_counters()
+	}
+	else {
+		None
+	}
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::thread_perf_counters;
+
+#[cfg(not(target_os = "linux"))]
+pub fn thread_perf_counters() -> Option<PerfCounters> {
+	None
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+	use std::cell::OnceCell;
+	use std::fs::File;
+	use std::io::Read;
+	use std::os::fd::FromRawFd;
+	use super::{PerfCounter, PerfCounters};
+
+	const PERF_TYPE_HARDWARE: u32 = 0;
+	const PERF_TYPE_SOFTWARE: u32 = 1;
+	const PERF_FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+	const PERF_FLAG_EXCLUDE_HV: u64 = 1 << 6;
+	const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+	// `struct perf_event_attr` up to PERF_ATTR_SIZE_VER5
+	#[repr(C)]
+	#[derive(Default)]
+	struct PerfEventAttr {
+		type_: u32,
+		size: u32,
+		config: u64,
+		sample_period: u64,
+		sample_type: u64,
+		read_format: u64,
+		flags: u64,
+		wakeup_events: u32,
+		bp_type: u32,
+		config1: u64,
+		config2: u64,
+		branch_sample_type: u64,
+		sample_regs_user: u64,
+		sample_stack_user: u32,
+		clockid: i32,
+		sample_regs_intr: u64,
+		aux_watermark: u32,
+		sample_max_stack: u16,
+		reserved: u16,
+	}
+
+	fn type_and_config(counter: PerfCounter) -> (u32, u64) {
+		match counter {
+			PerfCounter::Cycles => (PERF_TYPE_HARDWARE, 0),
+			PerfCounter::Instructions => (PERF_TYPE_HARDWARE, 1),
+			PerfCounter::CacheReferences => (PERF_TYPE_HARDWARE, 2),
+			PerfCounter::CacheMisses => (PERF_TYPE_HARDWARE, 3),
+			PerfCounter::BranchInstructions => (PERF_TYPE_HARDWARE, 4),
+			PerfCounter::BranchMisses => (PERF_TYPE_HARDWARE, 5),
+			PerfCounter::TaskClock => (PERF_TYPE_SOFTWARE, 1),
+			PerfCounter::PageFaults => (PERF_TYPE_SOFTWARE, 2),
+			PerfCounter::ContextSwitches => (PERF_TYPE_SOFTWARE, 3),
+			PerfCounter::CpuMigrations => (PERF_TYPE_SOFTWARE, 4),
+		}
+	}
+
+	// counts only the calling thread, on any cpu
+	fn open(counter: PerfCounter) -> Option<File> {
+		let (type_, config) = type_and_config(counter);
+		if type_ == PERF_TYPE_HARDWARE {
+			return open_event(type_, config, PERF_FLAG_EXCLUDE_KERNEL | PERF_FLAG_EXCLUDE_HV);
+		}
+		// software events are counted in the kernel, excluding it would leave context switches and migrations at 0.
+		// Where the kernel can't be profiled (perf_event_paranoid > 1) only task clock and page faults still count
+		open_event(type_, config, 0).or_else(|| match counter {
+			PerfCounter::TaskClock | PerfCounter::PageFaults => open_event(type_, config, PERF_FLAG_EXCLUDE_KERNEL | PERF_FLAG_EXCLUDE_HV),
+			_ => None,
+		})
+	}
+
+	fn open_event(type_: u32, config: u64, flags: u64) -> Option<File> {
+		let attr = PerfEventAttr {
+			type_,
+			size: std::mem::size_of::<PerfEventAttr>() as u32,
+			config,
+			flags,
+			..Default::default()
+		};
+		let fd = unsafe { libc::syscall(libc::SYS_perf_event_open, &attr as *const PerfEventAttr, 0, -1, -1, PERF_FLAG_FD_CLOEXEC) };
+		if fd < 0 {
+			return None;
+		}
+		Some(unsafe { File::from_raw_fd(fd as i32) })
+	}
+
+	struct ThreadCounters {
+		counters: Vec<(PerfCounter, File)>,
+	}
+
+	impl ThreadCounters {
+		fn open() -> Option<Self> {
+			let open_all = |counters: &[PerfCounter]| -> Vec<(PerfCounter, File)> {
+				counters.iter().filter_map(|counter| Some((*counter, open(*counter)?))).collect()
+			};
+			let mut counters = open_all(&PerfCounter::HARDWARE);
+			if counters.is_empty() {
+				counters = open_all(&PerfCounter::SOFTWARE);
+			}
+			if counters.is_empty() {
+				None
+			}
+			else {
+				Some(Self { counters })
+			}
+		}
+
+		fn read(&self) -> PerfCounters {
+			let mut values = PerfCounters::default();
+			for (counter, file) in self.counters.iter() {
+				let mut bytes = [0u8; 8];
+				if (&mut &*file).read_exact(&mut bytes).is_ok() {
+					values.set(*counter, u64::from_ne_bytes(bytes));
+				}
+			}
+			values
+		}
+	}
+
+	thread_local! {
+		static THREAD_COUNTERS: OnceCell<Option<ThreadCounters>> = const { OnceCell::new() };
+	}
+
+	pub fn thread_perf_counters() -> Option<PerfCounters> {
+		THREAD_COUNTERS.try_with(|counters| {
+			counters.get_or_init(ThreadCounters::open).as_ref().map(ThreadCounters::read)
+		}).ok().flatten()
+	}
+}
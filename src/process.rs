@@ -0,0 +1,96 @@
+// Profiling across processes: a parent shares its session through environment variables that it sets on the
+// processes it spawns, see `CommandExt::share_session`, every child saves its recording into the shared directory on the
+// time base of the parent, and the parent merges them into its own session as one group of threads per process.
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+#[cfg(not(target_os = "linux"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::{Context, Result};
+use crate::{GlobalProfiler, GLOBAL_PROFILER, PROGRAM_START};
+pub use profiler_core::current_process_name;
+
+// the shared output directory
+pub const SESSION_DIR_VAR: &str = "PROFILER_SESSION_DIR";
+// the program start of the parent in nanoseconds on the clock of `shared_clock`
+pub const SESSION_START_VAR: &str = "PROFILER_SESSION_START";
+
+// the same for all processes of a machine, CLOCK_MONOTONIC is not moved by NTP or by setting the clock
+#[cfg(target_os = "linux")]
+fn shared_clock() -> Duration {
+	let mut time: libc::timespec = unsafe { std::mem::zeroed() };
+	unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) };
+	Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+// the wall clock, changing the system time between the start of the parent and of a child misplaces the child
+#[cfg(not(target_os = "linux"))]
+fn shared_clock() -> Duration {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+// `PROGRAM_START` on the shared clock
+fn program_start_time() -> Duration {
+	shared_clock().saturating_sub(PROGRAM_START.elapsed())
+}
+
+/// The environment variables that make a child process join the session of this process, it saves its recording to `dir`.
+/// Creates `dir`, the variables are meant for `Command::envs`, this process keeps its own environment.
+pub fn session_env<P: AsRef<Path>>(dir: P) -> Result<[(&'static str, OsString); 2]> {
+	let dir = dir.as_ref();
+	std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+	Ok([
+		(SESSION_DIR_VAR, dir.as_os_str().to_owned()),
+		(SESSION_START_VAR, program_start_time().as_nanos().to_string().into()),
+	])
+}
+
+pub trait CommandExt {
+	/// Makes the spawned process join the session of this process, see `session_env`
+	fn share_session<P: AsRef<Path>>(&mut self, dir: P) -> Result<&mut Self>;
+}
+
+impl CommandExt for Command {
+	fn share_session<P: AsRef<Path>>(&mut self, dir: P) -> Result<&mut Self> {
+		Ok(self.envs(session_env(dir)?))
+	}
+}
+
+/// The shared output directory if this process was spawned by a process that shares its session
+pub fn parent_session_dir() -> Option<PathBuf> {
+	std::env::var_os(SESSION_DIR_VAR).map(PathBuf::from)
+}
+
+/// Saves everything this process recorded into the directory of the parent session, on the time base of the parent.
+/// Returns `false` without saving if this process was not spawned by a process that shares its session.
+pub fn save_to_parent_session() -> Result<bool> {
+	let Some(dir) = parent_session_dir() else {
+		return Ok(false);
+	};
+	let parent_start = std::env::var(SESSION_START_VAR)
+		.context("Parent session start is missing")?
+		.parse::<u64>()
+		.context("Parent session start is not a number")?;
+	let mut global_profiler = GLOBAL_PROFILER.lock().unwrap().clone();
+	// children start after their parent, a clock that went backwards merely puts them at its start
+	global_profiler.shift_times(program_start_time().saturating_sub(Duration::from_nanos(parent_start)));
+	global_profiler.save_to_file(dir.join(format!("{}.profiling", std::process::id())))?;
+	Ok(true)
+}
+
+/// Merges the recordings that child processes saved into `dir` into `GLOBAL_PROFILER`, returns how many were merged
+pub fn merge_child_sessions<P: AsRef<Path>>(dir: P) -> Result<usize> {
+	let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+		.map(|entry| entry.map(|entry| entry.path()))
+		.collect::<std::io::Result<_>>()?;
+	paths.retain(|path| path.extension().is_some_and(|extension| extension == "profiling"));
+	paths.sort();
+	let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	for path in paths.iter() {
+		let mut child = GlobalProfiler::new();
+		child.load_from_file(path).with_context(|| format!("Failed to load {}", path.display()))?;
+		global_profiler.merge_process(child);
+	}
+	Ok(paths.len())
+}
@@ -0,0 +1,99 @@
+// Rayon integration.
+// `instrument_thread_pool` names the pool threads and makes them submit frames of kind `WORKER_FRAME_KIND`
+// when their outermost scope ends, batched by `Profiler::auto_submit_frames`, so that their scopes are published
+// without calling `submit_frame!`. `submit_worker_frames` publishes the last batch of every worker right away.
+// `join`, `spawn` and `in_parent` remember the innermost scope of the calling thread,
+// and top level scopes of work that ran on another thread are linked to it through `ScopeResult::parent`.
+use rayon::ThreadPoolBuilder;
+use crate::ScopeParent;
+#[cfg(feature = "enable_profiling")]
+use crate::PROFILER;
+
+pub const WORKER_FRAME_KIND: &str = "rayon_worker";
+
+// replaces the thread name, start and exit handlers of the builder
+pub fn instrument_thread_pool(builder: ThreadPoolBuilder) -> ThreadPoolBuilder {
+	builder
+		.thread_name(|index| format!("rayon-worker-{index}"))
+		.start_handler(|_| on_thread_start())
+		.exit_handler(|_| on_thread_exit())
+}
+
+fn on_thread_start() {
+	#[cfg(feature = "enable_profiling")]
+	PROFILER.with_borrow_mut(|p| p.auto_submit_frames(Some(WORKER_FRAME_KIND)));
+}
+
+fn on_thread_exit() {
+	#[cfg(feature = "enable_profiling")]
+	PROFILER.with_borrow_mut(|p| p.submit_frame_of_kind(WORKER_FRAME_KIND));
+}
+
+// submits the frames the workers of the current pool collected so far, e.g. right before the calling thread submits its own frame
+pub fn submit_worker_frames() {
+	#[cfg(feature = "enable_profiling")]
+	rayon::broadcast(|_| PROFILER.with_borrow_mut(|p| p.flush_auto_submit_frame()));
+}
+
+fn current_parent() -> Option<ScopeParent> {
+	#[cfg(feature = "enable_profiling")]
+	return PROFILER.with_borrow(|p| p.current_parent());
+	#[cfg(not(feature = "enable_profiling"))]
+	None
+}
+
+// runs `f` with its top level scopes attributed to `parent`, unless it runs on the parent thread itself
+#[cfg(feature = "enable_profiling")]
+fn with_parent<R>(parent: Option<ScopeParent>, f: impl FnOnce() -> R) -> R {
+	let Some(parent) = parent.filter(|parent| parent.thread != crate::get_current_thread_id_u64()) else {
+		return f();
+	};
+	let previous = PROFILER.with_borrow_mut(|p| p.set_inherited_parent(Some(parent)));
+	let _restore = RestoreParent(previous);
+	f()
+}
+
+// restores the parent even if the job panics, the worker thread keeps running other jobs
+#[cfg(feature = "enable_profiling")]
+struct RestoreParent(Option<ScopeParent>);
+
+#[cfg(feature = "enable_profiling")]
+impl Drop for RestoreParent {
+	fn drop(&mut self) {
+		PROFILER.with_borrow_mut(|p| p.set_inherited_parent(self.0));
+	}
+}
+
+#[cfg(not(feature = "enable_profiling"))]
+fn with_parent<R>(_parent: Option<ScopeParent>, f: impl FnOnce() -> R) -> R {
+	f()
+}
+
+// `rayon::join` that attributes a stolen closure to the calling scope
+pub fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+where
+	A: FnOnce() -> RA + Send,
+	B: FnOnce() -> RB + Send,
+	RA: Send,
+	RB: Send,
+{
+	let parent = current_parent();
+	rayon::join(move || with_parent(parent, oper_a), move || with_parent(parent, oper_b))
+}
+
+// `rayon::spawn` that attributes the job to the calling scope
+pub fn spawn<F>(f: F)
+where F: FnOnce() + Send + 'static
+{
+	let parent = current_parent();
+	rayon::spawn(move || with_parent(parent, f));
+}
+
+// wraps the closure of a parallel iterator adapter, e.g. `par_iter().for_each(in_parent(|item| ...))`,
+// so that the calls that run on worker threads are attributed to the calling scope
+pub fn in_parent<T, R, F>(f: F) -> impl Fn(T) -> R + Send + Sync
+where F: Fn(T) -> R + Send + Sync
+{
+	let parent = current_parent();
+	move |item| with_parent(parent, || f(item))
+}
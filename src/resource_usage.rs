@@ -0,0 +1,50 @@
+// Per-thread cpu time and os counters, sampled at scope entry and exit when enabled.
+// Only implemented on linux, every other platform reports no resource usage.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+pub use profiler_core::ResourceUsage;
+
+static RESOURCE_USAGE_SAMPLING: AtomicBool = AtomicBool::new(false);
+
+// costs two syscalls per scope entry and exit, so it is off by default
+pub fn enable_resource_usage_sampling(enabled: bool) {
+	RESOURCE_USAGE_SAMPLING.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_resource_usage_sampling_enabled() -> bool {
+	RESOURCE_USAGE_SAMPLING.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "enable_profiling")]
+pub(crate) fn sample() -> Option<ResourceUsage> {
+	if is_resource_usage_sampling_enabled() {
+		thread_resource_usage()
+	}
+	else {
+		None
+	}
+}
+
+#[cfg(target_os = "linux")]
+pub fn thread_resource_usage() -> Option<ResourceUsage> {
+	let mut cpu_time: libc::timespec = unsafe { std::mem::zeroed() };
+	if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut cpu_time) } != 0 {
+		return None;
+	}
+	let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+	if unsafe { libc::getrusage(libc::RUSAGE_THREAD, &mut usage) } != 0 {
+		return None;
+	}
+	Some(ResourceUsage {
+		cpu_time: Duration::new(cpu_time.tv_sec as u64, cpu_time.tv_nsec as u32),
+		voluntary_context_switches: usage.ru_nvcsw as u64,
+		involuntary_context_switches: usage.ru_nivcsw as u64,
+		minor_page_faults: usage.ru_minflt as u64,
+		major_page_faults: usage.ru_majflt as u64,
+	})
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn thread_resource_usage() -> Option<ResourceUsage> {
+	None
+}
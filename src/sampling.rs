@@ -0,0 +1,327 @@
+// Statistical sampling of registered threads on linux.
+// A sampler thread periodically sends SIGPROF to every registered thread, whose signal handler
+// walks the frame pointers of the interrupted call stack into a per-thread ring buffer.
+// The raw addresses are added to the current frame the next time that thread submits a frame,
+// and symbolised once they leave the thread, see `GlobalProfiler::symbolize_samples` and `profiler_core::set_symbolizer`.
+// Stacks are only complete for code built with frame pointers, e.g. `RUSTFLAGS="-C force-frame-pointers=yes"`,
+// otherwise they end at the first function without them, see `start_sampling`.
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use crate::{Sample, PROGRAM_START};
+
+const MAX_STACK_DEPTH: usize = 64;
+const BUFFER_CAPACITY: usize = 1024;
+
+struct RawSample {
+	// nanoseconds since `PROGRAM_START`
+	time: AtomicU64,
+	depth: AtomicUsize,
+	ips: [AtomicUsize; MAX_STACK_DEPTH],
+}
+
+// written by the signal handler and read by the owning thread, so both sides run on the same thread
+struct SampleBuffer {
+	samples: Vec<RawSample>,
+	written: AtomicUsize,
+	read: AtomicUsize,
+	// the highest address of the thread's stack, frame pointers above it are not followed
+	stack_end: usize,
+}
+
+impl SampleBuffer {
+	fn new(stack_end: usize) -> Self {
+		Self {
+			samples: (0..BUFFER_CAPACITY).map(|_| RawSample {
+				time: AtomicU64::new(0),
+				depth: AtomicUsize::new(0),
+				ips: std::array::from_fn(|_| AtomicUsize::new(0)),
+			}).collect(),
+			written: AtomicUsize::new(0),
+			read: AtomicUsize::new(0),
+			stack_end,
+		}
+	}
+}
+
+struct RegisteredThread {
+	tid: libc::pid_t,
+	// keeps the buffer alive while the signal handler may write into it
+	_buffer: Arc<SampleBuffer>,
+}
+
+struct Sampler {
+	threads: Vec<RegisteredThread>,
+	sampler_thread: Option<JoinHandle<()>>,
+	// the SIGPROF action before sampling started, restored when it stops
+	previous_action: Option<libc::sigaction>,
+}
+
+static SAMPLER: Lazy<Mutex<Sampler>> = Lazy::new(|| Mutex::new(Sampler {
+	threads: Vec::new(),
+	sampler_thread: None,
+	previous_action: None,
+}));
+static SAMPLING: AtomicBool = AtomicBool::new(false);
+static SYMBOL_CACHE: Lazy<Mutex<HashMap<usize, Vec<String>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+	// only touched by the signal handler, so it must not need lazy initialization or a destructor
+	static CURRENT_BUFFER: Cell<*const SampleBuffer> = const { Cell::new(std::ptr::null()) };
+	static REGISTRATION: RefCell<Option<Registration>> = const { RefCell::new(None) };
+}
+
+// unregisters the thread when it exits
+struct Registration {
+	tid: libc::pid_t,
+}
+
+impl Drop for Registration {
+	fn drop(&mut self) {
+		let _ = CURRENT_BUFFER.try_with(|buffer| buffer.set(std::ptr::null()));
+		SAMPLER.lock().unwrap().threads.retain(|thread| thread.tid != self.tid);
+	}
+}
+
+// only registered threads are sampled
+pub fn register_current_thread() {
+	REGISTRATION.with_borrow_mut(|registration| {
+		if registration.is_some() {
+			return;
+		}
+		let tid = unsafe { libc::gettid() };
+		let buffer = Arc::new(SampleBuffer::new(current_stack_end()));
+		CURRENT_BUFFER.with(|current_buffer| current_buffer.set(Arc::as_ptr(&buffer)));
+		SAMPLER.lock().unwrap().threads.push(RegisteredThread { tid, _buffer: buffer });
+		*registration = Some(Registration { tid });
+	});
+}
+
+// 0 if it is unknown, which leaves samples with only the interrupted instruction
+fn current_stack_end() -> usize {
+	unsafe {
+		let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+		if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+			return 0;
+		}
+		let mut stack_start: *mut libc::c_void = std::ptr::null_mut();
+		let mut stack_size = 0;
+		let result = libc::pthread_attr_getstack(&attr, &mut stack_start, &mut stack_size);
+		libc::pthread_attr_destroy(&mut attr);
+		if result != 0 {
+			return 0;
+		}
+		stack_start as usize + stack_size
+	}
+}
+
+pub fn is_sampling() -> bool {
+	SAMPLING.load(Ordering::Relaxed)
+}
+
+// Stacks are walked through frame pointers. The caller of the interrupted function is only found if that function
+// keeps a frame pointer or was interrupted while entering or leaving, so a sample taken in the middle of a leaf
+// function built without frame pointers skips straight from it to its caller's caller.
+pub fn start_sampling(interval: Duration) {
+	let mut sampler = SAMPLER.lock().unwrap();
+	if sampler.sampler_thread.is_some() {
+		return;
+	}
+	Lazy::force(&PROGRAM_START);
+	profiler_core::set_symbolizer(symbolize);
+	sampler.previous_action = Some(install_signal_handler());
+	SAMPLING.store(true, Ordering::Relaxed);
+	sampler.sampler_thread = Some(std::thread::Builder::new()
+		.name("profiler sampler".to_string())
+		.spawn(move || {
+			let pid = unsafe { libc::getpid() };
+			while SAMPLING.load(Ordering::Relaxed) {
+				for thread in SAMPLER.lock().unwrap().threads.iter() {
+					unsafe { libc::syscall(libc::SYS_tgkill, pid, thread.tid, libc::SIGPROF) };
+				}
+				std::thread::sleep(interval);
+			}
+		})
+		.expect("Failed to spawn sampler thread"));
+}
+
+pub fn stop_sampling() {
+	SAMPLING.store(false, Ordering::Relaxed);
+	let sampler_thread = SAMPLER.lock().unwrap().sampler_thread.take();
+	if let Some(sampler_thread) = sampler_thread {
+		sampler_thread.join().unwrap();
+	}
+	if let Some(previous_action) = SAMPLER.lock().unwrap().previous_action.take() {
+		restore_signal_handler(previous_action);
+	}
+}
+
+// returns the previous action
+fn install_signal_handler() -> libc::sigaction {
+	unsafe {
+		let mut action: libc::sigaction = std::mem::zeroed();
+		action.sa_sigaction = signal_handler as *const () as libc::sighandler_t;
+		action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+		libc::sigemptyset(&mut action.sa_mask);
+		let mut previous_action: libc::sigaction = std::mem::zeroed();
+		libc::sigaction(libc::SIGPROF, &action, &mut previous_action);
+		previous_action
+	}
+}
+
+fn restore_signal_handler(mut previous_action: libc::sigaction) {
+	// a sample that is still on its way would terminate the process with the default action
+	if previous_action.sa_sigaction == libc::SIG_DFL {
+		previous_action.sa_sigaction = libc::SIG_IGN;
+	}
+	unsafe {
+		libc::sigaction(libc::SIGPROF, &previous_action, std::ptr::null_mut());
+	}
+}
+
+// the instruction, stack pointer and frame pointer the thread was interrupted at
+#[cfg(target_arch = "x86_64")]
+unsafe fn interrupted_registers(context: *mut libc::c_void) -> Option<(usize, usize, usize)> {
+	let context = &*(context as *const libc::ucontext_t);
+	let registers = &context.uc_mcontext.gregs;
+	Some((registers[libc::REG_RIP as usize] as usize, registers[libc::REG_RSP as usize] as usize, registers[libc::REG_RBP as usize] as usize))
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn interrupted_registers(context: *mut libc::c_void) -> Option<(usize, usize, usize)> {
+	let context = &*(context as *const libc::ucontext_t);
+	Some((context.uc_mcontext.pc as usize, context.uc_mcontext.sp as usize, context.uc_mcontext.regs[29] as usize))
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+unsafe fn interrupted_registers(_context: *mut libc::c_void) -> Option<(usize, usize, usize)> {
+	None
+}
+
+// the return address of an interrupted function that hasn't pushed its frame record yet or has already popped it,
+// in which case the frame pointer still belongs to its caller and the walk would skip that caller
+#[cfg(target_arch = "x86_64")]
+unsafe fn unframed_return_address(ip: usize, sp: usize, _context: *mut libc::c_void) -> Option<usize> {
+	let code = ip as *const u8;
+	match *code {
+		// `push rbp` at the entry and `ret` at the exit find the return address on top of the stack
+		0x55 | 0xc3 => Some(*(sp as *const usize)),
+		// `mov rbp, rsp` right after pushing the caller's frame pointer
+		0x48 if *code.add(1) == 0x89 && *code.add(2) == 0xe5 => Some(*((sp as *const usize).add(1))),
+		_ => None,
+	}
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn unframed_return_address(ip: usize, _sp: usize, context: *mut libc::c_void) -> Option<usize> {
+	let context = &*(context as *const libc::ucontext_t);
+	let instruction = *(ip as *const u32);
+	// `stp x29, x30, [sp, #-n]!` at the entry and `ret` at the exit find the return address in the link register
+	let is_frame_push = instruction & 0xffc0_0000 == 0xa980_0000 && instruction & 0x7fff == (30 << 10) | (31 << 5) | 29;
+	if is_frame_push || instruction == 0xd65f_03c0 {
+		Some(context.uc_mcontext.regs[30] as usize)
+	} else {
+		None
+	}
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+unsafe fn unframed_return_address(_ip: usize, _sp: usize, _context: *mut libc::c_void) -> Option<usize> {
+	None
+}
+
+extern "C" fn signal_handler(_signal: libc::c_int, _info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+	let Ok(buffer) = CURRENT_BUFFER.try_with(|buffer| buffer.get()) else {
+		return;
+	};
+	if buffer.is_null() {
+		return;
+	}
+	let buffer = unsafe { &*buffer };
+	let written = buffer.written.load(Ordering::Relaxed);
+	if written - buffer.read.load(Ordering::Relaxed) >= BUFFER_CAPACITY {
+		// full, the thread hasn't submitted a frame in a while
+		return;
+	}
+
+	let Some((ip, sp, mut fp)) = (unsafe { interrupted_registers(context) }) else {
+		return;
+	};
+	let sample = &buffer.samples[written % BUFFER_CAPACITY];
+	sample.time.store(PROGRAM_START.elapsed().as_nanos() as u64, Ordering::Relaxed);
+	sample.ips[0].store(ip, Ordering::Relaxed);
+	let mut depth = 1;
+	if sp % std::mem::align_of::<usize>() == 0 && sp.saturating_add(2 * std::mem::size_of::<usize>()) <= buffer.stack_end {
+		if let Some(return_address) = unsafe { unframed_return_address(ip, sp, context) } {
+			sample.ips[depth].store(return_address, Ordering::Relaxed);
+			depth += 1;
+		}
+	}
+	// every frame record holds the caller's frame pointer followed by the return address.
+	// Only records between the interrupted stack pointer and the end of the stack are read, and every one
+	// has to be above the previous one, so a function without frame pointers ends the walk instead of faulting.
+	let record_size = 2 * std::mem::size_of::<usize>();
+	let mut lowest = sp;
+	while depth < MAX_STACK_DEPTH && fp >= lowest && fp % std::mem::align_of::<usize>() == 0 && fp.saturating_add(record_size) <= buffer.stack_end {
+		let (caller_fp, return_address) = unsafe { (*(fp as *const usize), *((fp as *const usize).add(1))) };
+		if return_address == 0 {
+			break;
+		}
+		sample.ips[depth].store(return_address, Ordering::Relaxed);
+		depth += 1;
+		lowest = fp + record_size;
+		fp = caller_fp;
+	}
+	sample.depth.store(depth, Ordering::Relaxed);
+	buffer.written.store(written + 1, Ordering::Release);
+}
+
+// the samples the current thread recorded since the last call, not yet symbolised
+pub(crate) fn take_samples() -> Vec<Sample> {
+	let buffer = CURRENT_BUFFER.with(|buffer| buffer.get());
+	if buffer.is_null() {
+		return Vec::new();
+	}
+	let buffer = unsafe { &*buffer };
+	let written = buffer.written.load(Ordering::Acquire);
+	let mut samples = Vec::new();
+	for i in buffer.read.load(Ordering::Relaxed)..written {
+		let raw_sample = &buffer.samples[i % BUFFER_CAPACITY];
+		let addresses: Vec<u64> = raw_sample.ips[..raw_sample.depth.load(Ordering::Relaxed)].iter()
+			.map(|ip| ip.load(Ordering::Relaxed) as u64)
+			.collect();
+		let time = Duration::from_nanos(raw_sample.time.load(Ordering::Relaxed));
+		samples.push(Sample::new(time, addresses));
+	}
+	buffer.read.store(written, Ordering::Relaxed);
+	samples
+}
+
+// the addresses only mean something in this process, so samples are symbolised before they leave it
+fn symbolize(samples: &mut [Sample]) {
+	let mut symbol_cache = SYMBOL_CACHE.lock().unwrap();
+	for sample in samples.iter_mut().filter(|sample| sample.stack.is_empty()) {
+		for (i, address) in sample.addresses.iter().enumerate() {
+			// return addresses point behind the call, which can be the first instruction of the next line or function
+			let address = if i == 0 { *address as usize } else { *address as usize - 1 };
+			// one address resolves to several functions when they were inlined into each other
+			let names = symbol_cache.entry(address).or_insert_with(|| {
+				let mut names = Vec::new();
+				backtrace::resolve(address as *mut std::ffi::c_void, |symbol| {
+					if let Some(name) = symbol.name() {
+						names.push(format!("{name:#}"));
+					}
+				});
+				if names.is_empty() {
+					names.push(format!("{address:#x}"));
+				}
+				names
+			});
+			sample.stack.extend(names.iter().cloned());
+		}
+	}
+}
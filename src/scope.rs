@@ -1,26 +1,64 @@
 
 
+#[cfg(feature = "enable_profiling")]
+use std::marker::PhantomData;
 #[cfg(feature = "enable_profiling")]
 use std::time::Instant;
 use std::time::Duration;
-use serde::{Serialize, Deserialize};
+pub use profiler_core::{ScopeResult, ScopeKind, ScopeParent};
 #[cfg(feature = "enable_profiling")]
-use crate::PROFILER;
+use crate::{PROFILER, current_global_frame};
 
 #[derive(Clone)]
 #[cfg(feature = "enable_profiling")]
 pub struct Scope {
 	pub name: String,
     pub start: Instant,
+	pub global_frame: Option<u64>,
+	pub kind: ScopeKind,
+	id: u64,
+	// a scope has to end on the thread it started on, its open scope is on that thread's profiler
+	_not_send: PhantomData<*const ()>,
 }
 
 #[cfg(feature = "enable_profiling")]
 impl Scope {
 	pub fn new(name: String) -> Self {
-		PROFILER.with_borrow_mut(|p| p.begin_profile_result());
+		Self::with_kind(name, ScopeKind::Cpu)
+	}
+
+	pub fn with_kind(name: String, kind: ScopeKind) -> Self {
+		let id = PROFILER.with_borrow_mut(|p| p.begin_profile_result());
 		Self {
 			name,
 			start: Instant::now(),
+			global_frame: current_global_frame(),
+			kind,
+			id,
+			_not_send: PhantomData,
+		}
+	}
+
+	// bytes transferred while the scope is open
+	pub fn set_bytes(&self, bytes: u64) {
+		PROFILER.with_borrow_mut(|p| p.set_scope_bytes(self.id, bytes));
+	}
+
+	// links the scope to the `TaskSpan` of an instrumented future
+	pub fn set_task(&self, task: u64) {
+		PROFILER.with_borrow_mut(|p| p.set_scope_task(self.id, task));
+	}
+
+	pub fn add_metadata(&self, key: String, value: String) {
+		PROFILER.with_borrow_mut(|p| p.add_scope_metadata(self.id, key, value));
+	}
+
+	// ends the scope like dropping it, unless the profiler of this thread was destroyed already.
+	// For scopes that thread locals keep, they can be destroyed after `PROFILER` when the thread exits
+	pub fn end_in_teardown(self) {
+		if PROFILER.try_with(|_| ()).is_err() {
+			let mut scope = std::mem::ManuallyDrop::new(self);
+			drop(std::mem::take(&mut scope.name));
 		}
 	}
 }
@@ -30,7 +68,7 @@ impl Drop for Scope {
 	fn drop(&mut self) {
 		let duration = self.start.elapsed();
 
-        PROFILER.with_borrow_mut(|p| p.submit_profile_result(self.name.clone(), self.start, duration));
+        PROFILER.with_borrow_mut(|p| p.submit_profile_result(self.id, std::mem::take(&mut self.name), self.start, duration, self.global_frame, self.kind));
     }
 }
 
@@ -50,28 +88,32 @@ macro_rules! scope {
 	};
 }
 
+// like `scope!`, but the time is counted as blocked instead of busy
+#[macro_export]
+#[cfg(feature = "enable_profiling")]
+macro_rules! wait_scope {
+	($name:expr) => {
+		let _scope = profiler::Scope::with_kind(format!("{}::{}", profiler::function_name!(), $name), profiler::ScopeKind::Wait);
+	};
+	($name:expr, $kind:expr) => {
+		let _scope = profiler::Scope::with_kind(format!("{}::{}", profiler::function_name!(), $name), $kind);
+	};
+}
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-pub struct ScopeResult {
-	pub name: String,
-    pub start: Duration,
-    pub duration: Duration,
-	pub depth: usize,
+#[macro_export]
+#[cfg(not(feature = "enable_profiling"))]
+macro_rules! wait_scope {
+	($name:expr) => {
+		
+	};
+	($name:expr, $kind:expr) => {
+		
+	};
 }
 
-impl ScopeResult {
-	pub fn new(name: String, start: Duration, duration: Duration, depth: usize) -> Self {
-		Self {
-            name,
-            start,
-            duration,
-			depth,
-        }
-	}
-	
-	pub fn is_inside(&self, other: &Self) -> bool {
-		let self_end = self.start + self.duration;
-		let other_end = other.start + other.duration;
-		self.start >= other.start && self_end <= other_end
-	}
+// `std::thread::sleep` recorded as a sleep scope
+pub fn sleep(duration: Duration) {
+	#[cfg(feature = "enable_profiling")]
+	let _scope = Scope::with_kind("std::thread::sleep".to_string(), ScopeKind::Sleep);
+	std::thread::sleep(duration);
 }
\ No newline at end of file
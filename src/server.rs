@@ -0,0 +1,113 @@
+// Streams every submitted frame over TCP to the viewers connected to this process, see `stream` for the framing.
+// A viewer only receives the frames that were submitted after it connected.
+use std::io::Write;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use anyhow::Result;
+use crate::Frame;
+use crate::sink::{Sink, SinkId, ThreadInfo, add_sink};
+use crate::stream::{StreamMessage, write_message};
+
+// localhost only, other machines can't connect unless a public address is passed to `start_server`
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7373";
+
+// frames waiting for the writer thread, newer frames are dropped while it is full
+const MESSAGE_QUEUE_LENGTH: usize = 256;
+// a viewer that doesn't take a frame within this time is disconnected
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+// how often the accept thread checks whether the sink was dropped
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct TcpStreamSink {
+	// frames for the writer thread, it stops once the sink is dropped
+	frames: SyncSender<(ThreadInfo, Frame)>,
+	address: SocketAddr,
+	stopped: Arc<AtomicBool>,
+	accept_thread: Option<JoinHandle<()>>,
+}
+
+impl TcpStreamSink {
+	// port 0 lets the os pick a free port, see `local_addr`
+	pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+		let listener = TcpListener::bind(address)?;
+		let address = listener.local_addr()?;
+		// polled, so that the listener is closed soon after the sink was dropped
+		listener.set_nonblocking(true)?;
+		let clients = Arc::new(Mutex::new(Vec::new()));
+		let accepted_clients = clients.clone();
+		let stopped = Arc::new(AtomicBool::new(false));
+		let accept_stopped = stopped.clone();
+		let accept_thread = std::thread::Builder::new().name("profiler server".to_string()).spawn(move || {
+			while !accept_stopped.load(Ordering::Relaxed) {
+				let client = match listener.accept() {
+					Ok((client, _)) => client,
+					// no client waiting, or e.g. out of file descriptors
+					Err(_) => {
+						std::thread::sleep(ACCEPT_INTERVAL);
+						continue;
+					},
+				};
+				if client.set_nonblocking(false).is_err() || client.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+					continue;
+				}
+				let _ = client.set_nodelay(true);
+				accepted_clients.lock().unwrap().push(client);
+			}
+		})?;
+		// a slow viewer holds up the writer thread, never the threads that submit frames
+		let (frames, received_frames) = sync_channel::<(ThreadInfo, Frame)>(MESSAGE_QUEUE_LENGTH);
+		std::thread::Builder::new().name("profiler server writer".to_string()).spawn(move || {
+			for (thread, mut frame) in received_frames {
+				// the viewer can't resolve addresses of this process
+				frame.symbolize_samples();
+				let mut bytes = Vec::new();
+				if write_message(&mut bytes, &StreamMessage::Frame { thread, frame }).is_err() {
+					continue;
+				}
+				// viewers that disconnected or stalled are dropped
+				clients.lock().unwrap().retain_mut(|client| client.write_all(&bytes).is_ok());
+			}
+			// closes the connections once the sink was removed
+			clients.lock().unwrap().clear();
+		})?;
+		Ok(Self {
+			frames,
+			address,
+			stopped,
+			accept_thread: Some(accept_thread),
+		})
+	}
+
+	pub fn local_addr(&self) -> SocketAddr {
+		self.address
+	}
+}
+
+impl Drop for TcpStreamSink {
+	// the address can be bound again once the sink is dropped
+	fn drop(&mut self) {
+		self.stopped.store(true, Ordering::Relaxed);
+		if let Some(accept_thread) = self.accept_thread.take() {
+			let _ = accept_thread.join();
+		}
+	}
+}
+
+impl Sink for TcpStreamSink {
+	fn submit_frame(&mut self, thread: &ThreadInfo, frame: &Frame) {
+		// dropped while the viewers fall behind
+		let _ = self.frames.try_send((thread.clone(), frame.clone()));
+	}
+}
+
+/// Streams every frame submitted from now on to the viewers that connect to `address`.
+/// Returns the id to stop streaming with `remove_sink` and the address the server listens on.
+pub fn start_server<A: ToSocketAddrs>(address: A) -> Result<(SinkId, SocketAddr)> {
+	let sink = TcpStreamSink::bind(address)?;
+	let address = sink.local_addr();
+	Ok((add_sink(sink), address))
+}
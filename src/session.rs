@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use anyhow::{Result, bail};
+use crate::{GlobalProfiler, GLOBAL_PROFILER};
+#[cfg(feature = "enable_profiling")]
+use crate::PROFILER;
+
+// advances whenever the recorded frames are replaced or cleared,
+// frames that threads started before are dropped when they are submitted
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+// from `start_session` until `end_session`
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn generation() -> u64 {
+	GENERATION.load(Ordering::Acquire)
+}
+
+// the frame the calling thread is recording started before, it starts over in the new generation
+fn advance_generation() {
+	GENERATION.fetch_add(1, Ordering::AcqRel);
+	#[cfg(feature = "enable_profiling")]
+	PROFILER.with_borrow_mut(|p| p.restart_frame());
+}
+
+/// Discards everything recorded so far and starts a new capture called `name`.
+/// Fails while the session started before is not ended with `end_session`.
+pub fn start_session<S: Into<String>>(name: S) -> Result<()> {
+	let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	if ACTIVE.swap(true, Ordering::AcqRel) {
+		bail!("Session '{}' is still active, end it with `end_session` first", global_profiler.session_name);
+	}
+	*global_profiler = GlobalProfiler::with_session_name(name.into());
+	advance_generation();
+	Ok(())
+}
+
+/// Takes the current capture out of `GLOBAL_PROFILER`, leaving an empty unnamed session behind
+pub fn end_session() -> GlobalProfiler {
+	let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let mut session = std::mem::take(&mut *global_profiler);
+	ACTIVE.store(false, Ordering::Release);
+	advance_generation();
+	drop(global_profiler);
+	session.symbolize_samples();
+	session
+}
+
+/// Clears all recorded frames but keeps the current session name
+pub fn reset() {
+	let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	global_profiler.thread_profilers.clear();
+	advance_generation();
+}
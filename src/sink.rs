@@ -0,0 +1,206 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::thread::JoinHandle;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use once_cell::sync::Lazy;
+use serde::{Serialize, Deserialize};
+use anyhow::Result;
+use crate::{control, Frame, ScopeResult, ThreadProfiler, GLOBAL_PROFILER};
+use crate::stream::{StreamMessage, write_message};
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ThreadInfo {
+	pub id: u64,
+	pub name: String,
+	pub defines_global_frames: bool,
+}
+
+impl ThreadInfo {
+	pub(crate) fn current() -> Self {
+		Self {
+			id: crate::get_current_thread_id_u64(),
+			name: crate::get_current_thread_name(),
+			defines_global_frames: false,
+		}
+	}
+}
+
+// Receives everything the thread profilers record.
+// Sinks are called while the sink registry is locked and the thread's profiler is borrowed,
+// so they must neither record scopes nor add or remove sinks themselves.
+pub trait Sink: Send {
+	fn submit_frame(&mut self, thread: &ThreadInfo, frame: &Frame);
+
+	fn submit_scope_result(&mut self, _thread: &ThreadInfo, _scope_result: &ScopeResult) {}
+
+	// scope results are only passed to sinks that ask for them
+	fn wants_scope_results(&self) -> bool {
+		false
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SinkId(u64);
+
+impl SinkId {
+	// the memory sink that is registered by default
+	pub const MEMORY: SinkId = SinkId(0);
+}
+
+struct Sinks {
+	next_id: u64,
+	sinks: Vec<(SinkId, Box<dyn Sink>)>,
+}
+
+static SINKS: Lazy<Mutex<Sinks>> = Lazy::new(|| Mutex::new(Sinks {
+	next_id: 1,
+	sinks: vec![(SinkId::MEMORY, Box::new(MemorySink))],
+}));
+static SCOPE_RESULT_SINK_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn add_sink<S: Sink + 'static>(sink: S) -> SinkId {
+	let mut sinks = SINKS.lock().unwrap();
+	let id = SinkId(sinks.next_id);
+	sinks.next_id += 1;
+	if sink.wants_scope_results() {
+		SCOPE_RESULT_SINK_COUNT.fetch_add(1, Ordering::Relaxed);
+	}
+	sinks.sinks.push((id, Box::new(sink)));
+	id
+}
+
+pub fn remove_sink(id: SinkId) -> Option<Box<dyn Sink>> {
+	let mut sinks = SINKS.lock().unwrap();
+	let index = sinks.sinks.iter().position(|(sink_id, _)| *sink_id == id)?;
+	let (_, sink) = sinks.sinks.remove(index);
+	if sink.wants_scope_results() {
+		SCOPE_RESULT_SINK_COUNT.fetch_sub(1, Ordering::Relaxed);
+	}
+	Some(sink)
+}
+
+pub(crate) fn submit_frame(thread: &ThreadInfo, frame: &Frame) {
+	if !control::record_frame(thread) {
+		return;
+	}
+	for (_, sink) in SINKS.lock().unwrap().sinks.iter_mut() {
+		sink.submit_frame(thread, frame);
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+pub(crate) fn submit_scope_result(thread: &ThreadInfo, scope_result: &ScopeResult) {
+	if SCOPE_RESULT_SINK_COUNT.load(Ordering::Relaxed) == 0 || !control::record_scope_result(thread) {
+		return;
+	}
+	for (_, sink) in SINKS.lock().unwrap().sinks.iter_mut() {
+		if sink.wants_scope_results() {
+			sink.submit_scope_result(thread, scope_result);
+		}
+	}
+}
+
+// stores all frames in `GLOBAL_PROFILER`
+pub struct MemorySink;
+
+impl Sink for MemorySink {
+	fn submit_frame(&mut self, thread: &ThreadInfo, frame: &Frame) {
+		let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+		let thread_profiler = global_profiler.thread_profilers
+			.entry(thread.id)
+			.or_insert_with(|| ThreadProfiler::new(thread.name.clone(), Vec::new()));
+		thread_profiler.defines_global_frames |= thread.defines_global_frames;
+		thread_profiler.frames.push(frame.clone());
+	}
+}
+
+// writes every frame to a file as soon as it is submitted, see `stream` for the format.
+// A writer thread symbolises the samples and writes the file, everything is written once the sink is dropped.
+pub struct FileStreamSink {
+	frames: Option<Sender<(ThreadInfo, Frame)>>,
+	writer_thread: Option<JoinHandle<()>>,
+}
+
+impl FileStreamSink {
+	pub fn create<P>(path: P) -> Result<Self>
+	where P: AsRef<Path>
+	{
+		let mut writer = BufWriter::new(File::create(path)?);
+		let (frames, received_frames) = channel::<(ThreadInfo, Frame)>();
+		let writer_thread = std::thread::Builder::new().name("profiler file writer".to_string()).spawn(move || {
+			for (thread, mut frame) in received_frames {
+				frame.symbolize_samples();
+				// a failing profiling output should never take the application down with it
+				if write_message(&mut writer, &StreamMessage::Frame { thread, frame }).is_ok() {
+					let _ = writer.flush();
+				}
+			}
+		})?;
+		Ok(Self {
+			frames: Some(frames),
+			writer_thread: Some(writer_thread),
+		})
+	}
+}
+
+impl Sink for FileStreamSink {
+	fn submit_frame(&mut self, thread: &ThreadInfo, frame: &Frame) {
+		if let Some(frames) = &self.frames {
+			let _ = frames.send((thread.clone(), frame.clone()));
+		}
+	}
+}
+
+impl Drop for FileStreamSink {
+	fn drop(&mut self) {
+		self.frames = None;
+		if let Some(writer_thread) = self.writer_thread.take() {
+			let _ = writer_thread.join();
+		}
+	}
+}
+
+type FrameCallback = Box<dyn FnMut(&ThreadInfo, &Frame) + Send>;
+type ScopeResultCallback = Box<dyn FnMut(&ThreadInfo, &ScopeResult) + Send>;
+
+pub struct CallbackSink {
+	frame_callback: FrameCallback,
+	scope_result_callback: Option<ScopeResultCallback>,
+}
+
+impl CallbackSink {
+	pub fn new<F>(frame_callback: F) -> Self
+	where F: FnMut(&ThreadInfo, &Frame) + Send + 'static
+	{
+		Self {
+			frame_callback: Box::new(frame_callback),
+			scope_result_callback: None,
+		}
+	}
+
+	pub fn with_scope_result_callback<F>(mut self, scope_result_callback: F) -> Self
+	where F: FnMut(&ThreadInfo, &ScopeResult) + Send + 'static
+	{
+		self.scope_result_callback = Some(Box::new(scope_result_callback));
+		self
+	}
+}
+
+impl Sink for CallbackSink {
+	fn submit_frame(&mut self, thread: &ThreadInfo, frame: &Frame) {
+		(self.frame_callback)(thread, frame);
+	}
+
+	fn submit_scope_result(&mut self, thread: &ThreadInfo, scope_result: &ScopeResult) {
+		if let Some(scope_result_callback) = &mut self.scope_result_callback {
+			scope_result_callback(thread, scope_result);
+		}
+	}
+
+	fn wants_scope_results(&self) -> bool {
+		self.scope_result_callback.is_some()
+	}
+}
@@ -0,0 +1,73 @@
+// Streamed profiling data is a sequence of messages.
+// Every message is a little endian u32 byte length followed by that many bytes of a bincode encoded `StreamMessage`.
+use std::io::{Read, Write, ErrorKind};
+use std::path::Path;
+use std::fs::File;
+use std::io::BufReader;
+use serde::{Serialize, Deserialize};
+use anyhow::{Result, bail};
+use crate::{Frame, GlobalProfiler, ThreadProfiler};
+use crate::sink::ThreadInfo;
+
+// longer messages are rejected, so that a peer that isn't a profiler can't make the reader allocate gigabytes
+pub const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum StreamMessage {
+	Frame {
+		thread: ThreadInfo,
+		frame: Frame,
+	},
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &StreamMessage) -> Result<()> {
+	let bytes = profiler_core::serialize(message)?;
+	if bytes.len() > MAX_MESSAGE_LENGTH {
+		bail!("message of {} bytes is longer than {} bytes", bytes.len(), MAX_MESSAGE_LENGTH);
+	}
+	writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+	writer.write_all(&bytes)?;
+	Ok(())
+}
+
+// returns `None` once the stream ended cleanly between two messages
+pub fn read_message<R: Read>(reader: &mut R) -> Result<Option<StreamMessage>> {
+	let mut length = [0u8; 4];
+	match reader.read_exact(&mut length) {
+		Ok(()) => {},
+		Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+		Err(e) => return Err(e.into()),
+	}
+	let length = u32::from_le_bytes(length) as usize;
+	if length > MAX_MESSAGE_LENGTH {
+		bail!("message of {} bytes is longer than {} bytes", length, MAX_MESSAGE_LENGTH);
+	}
+	let mut bytes = vec![0u8; length];
+	reader.read_exact(&mut bytes)?;
+	Ok(Some(profiler_core::deserialize(&bytes)?))
+}
+
+// adds the frame of `message` to the thread that submitted it
+pub fn add_stream_message(global_profiler: &mut GlobalProfiler, message: StreamMessage) {
+	match message {
+		StreamMessage::Frame { thread, frame } => {
+			let thread_profiler = global_profiler.thread_profilers
+				.entry(thread.id)
+				.or_insert_with(|| ThreadProfiler::new(thread.name, Vec::new()));
+			thread_profiler.defines_global_frames |= thread.defines_global_frames;
+			thread_profiler.frames.push(frame);
+		},
+	}
+}
+
+// loads a file written by `FileStreamSink`
+pub fn load_stream_from_file<P>(path: P) -> Result<GlobalProfiler>
+where P: AsRef<Path>
+{
+	let mut reader = BufReader::new(File::open(path)?);
+	let mut global_profiler = GlobalProfiler::new();
+	while let Some(message) = read_message(&mut reader)? {
+		add_stream_message(&mut global_profiler, message);
+	}
+	Ok(global_profiler)
+}
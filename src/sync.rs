@@ -0,0 +1,214 @@
+// Drop-in `Mutex` and `RwLock` wrappers that record lock contention.
+// Waiting for a contended lock and holding it show up as scopes on the thread timeline,
+// and every frame keeps per-lock statistics, see `GlobalProfiler::lock_contention`.
+pub mod mpsc;
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{LockResult, PoisonError, TryLockError};
+#[cfg(feature = "enable_profiling")]
+use std::time::Instant;
+#[cfg(feature = "enable_profiling")]
+use crate::{current_global_frame, Scope, ScopeKind, PROFILER};
+pub use profiler_core::LockStatistics;
+
+// what the guard records once the lock is released.
+// The held scope is closed by its id rather than through a `Scope`, which would make the guards `!Sync`
+struct Held {
+	#[cfg(feature = "enable_profiling")]
+	name: &'static str,
+	#[cfg(feature = "enable_profiling")]
+	acquired: Instant,
+	#[cfg(feature = "enable_profiling")]
+	scope_name: String,
+	#[cfg(feature = "enable_profiling")]
+	scope_id: u64,
+	#[cfg(feature = "enable_profiling")]
+	global_frame: Option<u64>,
+}
+
+impl Held {
+	// waits through `lock` if `try_lock` would block, recording the wait as its own scope
+	#[cfg_attr(not(feature = "enable_profiling"), allow(unused_variables))]
+	fn acquire<G>(name: &'static str, held_scope_name: &str, wait_scope_name: &str, try_lock: impl FnOnce() -> Result<G, TryLockError<G>>, lock: impl FnOnce() -> LockResult<G>) -> (LockResult<G>, Held) {
+		#[cfg(feature = "enable_profiling")]
+		let wait_start = Instant::now();
+		let (result, contended) = match try_lock() {
+			Ok(guard) => (Ok(guard), false),
+			Err(TryLockError::Poisoned(e)) => (Err(e), false),
+			Err(TryLockError::WouldBlock) => {
+				#[cfg(feature = "enable_profiling")]
+				let _wait_scope = Scope::with_kind(format!("{name} {wait_scope_name}"), ScopeKind::Lock);
+				(lock(), true)
+			},
+		};
+
+		#[cfg(feature = "enable_profiling")]
+		let held = {
+			let acquired = Instant::now();
+			let scope_id = PROFILER.with_borrow_mut(|p| {
+				let statistics = p.lock_statistics(name);
+				statistics.acquisitions += 1;
+				if contended {
+					let wait = acquired.duration_since(wait_start);
+					statistics.contended_acquisitions += 1;
+					statistics.total_wait += wait;
+					statistics.max_wait = statistics.max_wait.max(wait);
+				}
+				p.begin_profile_result()
+			});
+			Held {
+				name,
+				acquired,
+				scope_name: format!("{name} {held_scope_name}"),
+				scope_id,
+				global_frame: current_global_frame(),
+			}
+		};
+		#[cfg(not(feature = "enable_profiling"))]
+		let held = Held {};
+		(result, held)
+	}
+}
+
+impl Drop for Held {
+	fn drop(&mut self) {
+		#[cfg(feature = "enable_profiling")]
+		{
+			let held = self.acquired.elapsed();
+			PROFILER.with_borrow_mut(|p| {
+				p.lock_statistics(self.name).total_held += held;
+				p.submit_profile_result(self.scope_id, std::mem::take(&mut self.scope_name), self.acquired, held, self.global_frame, ScopeKind::Cpu);
+			});
+		}
+	}
+}
+
+fn map_lock_result<G, T>(result: LockResult<G>, map: impl FnOnce(G) -> T) -> LockResult<T> {
+	match result {
+		Ok(guard) => Ok(map(guard)),
+		Err(e) => Err(PoisonError::new(map(e.into_inner()))),
+	}
+}
+
+pub struct Mutex<T: ?Sized> {
+	name: &'static str,
+	inner: std::sync::Mutex<T>,
+}
+
+impl<T> Mutex<T> {
+	pub const fn new(name: &'static str, value: T) -> Self {
+		Self {
+			name,
+			inner: std::sync::Mutex::new(value),
+		}
+	}
+
+	pub fn into_inner(self) -> LockResult<T> {
+		self.inner.into_inner()
+	}
+}
+
+impl<T: ?Sized> Mutex<T> {
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+		let (result, held) = Held::acquire(self.name, "(locked)", "(waiting)", || self.inner.try_lock(), || self.inner.lock());
+		map_lock_result(result, |guard| MutexGuard { guard, _held: held })
+	}
+
+	pub fn get_mut(&mut self) -> LockResult<&mut T> {
+		self.inner.get_mut()
+	}
+}
+
+// the lock is released before the held scope ends, so fields are dropped in this order
+pub struct MutexGuard<'a, T: ?Sized> {
+	guard: std::sync::MutexGuard<'a, T>,
+	_held: Held,
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.guard
+	}
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.guard
+	}
+}
+
+pub struct RwLock<T: ?Sized> {
+	name: &'static str,
+	inner: std::sync::RwLock<T>,
+}
+
+impl<T> RwLock<T> {
+	pub const fn new(name: &'static str, value: T) -> Self {
+		Self {
+			name,
+			inner: std::sync::RwLock::new(value),
+		}
+	}
+
+	pub fn into_inner(self) -> LockResult<T> {
+		self.inner.into_inner()
+	}
+}
+
+impl<T: ?Sized> RwLock<T> {
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+		let (result, held) = Held::acquire(self.name, "(read locked)", "(waiting for read)", || self.inner.try_read(), || self.inner.read());
+		map_lock_result(result, |guard| RwLockReadGuard { guard, _held: held })
+	}
+
+	pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+		let (result, held) = Held::acquire(self.name, "(write locked)", "(waiting for write)", || self.inner.try_write(), || self.inner.write());
+		map_lock_result(result, |guard| RwLockWriteGuard { guard, _held: held })
+	}
+
+	pub fn get_mut(&mut self) -> LockResult<&mut T> {
+		self.inner.get_mut()
+	}
+}
+
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+	guard: std::sync::RwLockReadGuard<'a, T>,
+	_held: Held,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.guard
+	}
+}
+
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+	guard: std::sync::RwLockWriteGuard<'a, T>,
+	_held: Held,
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.guard
+	}
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.guard
+	}
+}
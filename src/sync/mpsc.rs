@@ -0,0 +1,209 @@
+// `std::sync::mpsc` channels that timestamp every message on send, which shows up as a short "(send)" scope.
+// Receiving records the queue latency and the remaining queue depth as an event on the
+// receiving thread, and every frame keeps per-channel statistics, see `GlobalProfiler::channel_statistics`.
+// Only the first `MAX_MESSAGE_EVENTS_PER_FRAME` messages of a channel per frame get an event, the statistics count all of them.
+use std::sync::{Arc, atomic::{AtomicI64, Ordering}};
+use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use std::time::{Duration, Instant};
+#[cfg(feature = "enable_profiling")]
+use crate::{Scope, ScopeKind, EventKind, PROFILER};
+pub use profiler_core::ChannelStatistics;
+
+// per channel and receiving thread, so that busy channels don't grow `Frame::events` without bound
+pub const MAX_MESSAGE_EVENTS_PER_FRAME: u64 = 64;
+
+struct Message<T> {
+	sent: Instant,
+	value: T,
+}
+
+// shared by all senders and the receiver of one channel
+struct Shared {
+	name: &'static str,
+	// counted once a message is queued, so the receiver can take it out before it was counted
+	queue_depth: AtomicI64,
+}
+
+impl Shared {
+	// called after the message was queued, senders blocked on a full channel don't count
+	fn sent(&self) {
+		let queue_depth = (self.queue_depth.fetch_add(1, Ordering::Relaxed) + 1).max(0) as u64;
+		#[cfg(feature = "enable_profiling")]
+		PROFILER.with_borrow_mut(|p| {
+			let statistics = p.channel_statistics(self.name);
+			statistics.sent += 1;
+			statistics.max_queue_depth = statistics.max_queue_depth.max(queue_depth);
+		});
+		#[cfg(not(feature = "enable_profiling"))]
+		let _ = queue_depth;
+	}
+
+	fn received<T>(&self, message: Message<T>) -> T {
+		let queue_depth = (self.queue_depth.fetch_sub(1, Ordering::Relaxed) - 1).max(0) as u64;
+		let latency = message.sent.elapsed();
+		#[cfg(feature = "enable_profiling")]
+		PROFILER.with_borrow_mut(|p| {
+			let statistics = p.channel_statistics(self.name);
+			statistics.received += 1;
+			statistics.total_latency += latency;
+			statistics.max_latency = statistics.max_latency.max(latency);
+			if statistics.received <= MAX_MESSAGE_EVENTS_PER_FRAME {
+				p.push_event(self.name.to_string(), EventKind::MessageReceived { latency, queue_depth });
+			}
+		});
+		#[cfg(not(feature = "enable_profiling"))]
+		let _ = (queue_depth, latency);
+		message.value
+	}
+}
+
+pub fn channel<T>(name: &'static str) -> (Sender<T>, Receiver<T>) {
+	let (sender, receiver) = std::sync::mpsc::channel();
+	let shared = Arc::new(Shared {
+		name,
+		queue_depth: AtomicI64::new(0),
+	});
+	(Sender { inner: sender, shared: shared.clone() }, Receiver { inner: receiver, shared })
+}
+
+pub fn sync_channel<T>(name: &'static str, bound: usize) -> (SyncSender<T>, Receiver<T>) {
+	let (sender, receiver) = std::sync::mpsc::sync_channel(bound);
+	let shared = Arc::new(Shared {
+		name,
+		queue_depth: AtomicI64::new(0),
+	});
+	(SyncSender { inner: sender, shared: shared.clone() }, Receiver { inner: receiver, shared })
+}
+
+pub struct Sender<T> {
+	inner: std::sync::mpsc::Sender<Message<T>>,
+	shared: Arc<Shared>,
+}
+
+impl<T> Sender<T> {
+	pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+		#[cfg(feature = "enable_profiling")]
+		let _scope = Scope::new(format!("{} (send)", self.shared.name));
+		self.inner.send(Message { sent: Instant::now(), value }).map_err(|e| SendError(e.0.value))?;
+		self.shared.sent();
+		Ok(())
+	}
+}
+
+impl<T> Clone for Sender<T> {
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone(),
+			shared: self.shared.clone(),
+		}
+	}
+}
+
+pub struct SyncSender<T> {
+	inner: std::sync::mpsc::SyncSender<Message<T>>,
+	shared: Arc<Shared>,
+}
+
+impl<T> SyncSender<T> {
+	// blocks while the channel is full, which shows up as a second "(send)" scope of kind `ScopeKind::Wait`
+	pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+		let message = match self.try_send(value) {
+			Ok(()) => return Ok(()),
+			Err(TrySendError::Disconnected(value)) => return Err(SendError(value)),
+			Err(TrySendError::Full(value)) => value,
+		};
+		#[cfg(feature = "enable_profiling")]
+		let _scope = Scope::with_kind(format!("{} (send)", self.shared.name), ScopeKind::Wait);
+		self.inner.send(Message { sent: Instant::now(), value: message }).map_err(|e| SendError(e.0.value))?;
+		self.shared.sent();
+		Ok(())
+	}
+
+	pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+		#[cfg(feature = "enable_profiling")]
+		let _scope = Scope::new(format!("{} (send)", self.shared.name));
+		self.inner.try_send(Message { sent: Instant::now(), value }).map_err(|e| match e {
+			TrySendError::Full(message) => TrySendError::Full(message.value),
+			TrySendError::Disconnected(message) => TrySendError::Disconnected(message.value),
+		})?;
+		self.shared.sent();
+		Ok(())
+	}
+}
+
+impl<T> Clone for SyncSender<T> {
+	fn clone(&self) -> Self {
+		Self {
+			inner: self.inner.clone(),
+			shared: self.shared.clone(),
+		}
+	}
+}
+
+pub struct Receiver<T> {
+	inner: std::sync::mpsc::Receiver<Message<T>>,
+	shared: Arc<Shared>,
+}
+
+impl<T> Receiver<T> {
+	pub fn name(&self) -> &'static str {
+		self.shared.name
+	}
+
+	// blocks while the channel is empty, which shows up as a "(waiting)" scope
+	pub fn recv(&self) -> Result<T, RecvError> {
+		let message = match self.inner.try_recv() {
+			Ok(message) => message,
+			Err(TryRecvError::Disconnected) => return Err(RecvError),
+			Err(TryRecvError::Empty) => {
+				#[cfg(feature = "enable_profiling")]
+				let _scope = Scope::with_kind(format!("{} (waiting)", self.shared.name), ScopeKind::Wait);
+				self.inner.recv()?
+			},
+		};
+		Ok(self.shared.received(message))
+	}
+
+	pub fn try_recv(&self) -> Result<T, TryRecvError> {
+		let message = self.inner.try_recv()?;
+		Ok(self.shared.received(message))
+	}
+
+	pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+		let message = match self.inner.try_recv() {
+			Ok(message) => message,
+			Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+			Err(TryRecvError::Empty) => {
+				#[cfg(feature = "enable_profiling")]
+				let _scope = Scope::with_kind(format!("{} (waiting)", self.shared.name), ScopeKind::Wait);
+				self.inner.recv_timeout(timeout)?
+			},
+		};
+		Ok(self.shared.received(message))
+	}
+
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter { receiver: self }
+	}
+}
+
+pub struct Iter<'a, T> {
+	receiver: &'a Receiver<T>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		self.receiver.recv().ok()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+	type Item = T;
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Iter<'a, T> {
+		self.iter()
+	}
+}
@@ -0,0 +1,132 @@
+// Tokio runtime integration.
+// `instrument_runtime` names the profiler lanes of the runtime threads "tokio-worker-{n}" and "tokio-blocking-{n}",
+// records the time a worker is parked as a wait scope and makes the threads submit frames of kind `WORKER_FRAME_KIND`,
+// batched by `Profiler::auto_submit_frames`, so that they are published without ever calling `submit_frame!` themselves.
+// `spawn` wraps the task in an `Instrumented` future, so that every poll is linked to its `TaskSpan`.
+// With `--cfg tokio_unstable` every poll of every task is recorded as well, tagged with the tokio task id.
+#[cfg(feature = "enable_profiling")]
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use ::tokio::runtime::Builder;
+#[cfg(feature = "enable_profiling")]
+use ::tokio::runtime::{Handle, RuntimeFlavor};
+use ::tokio::task::JoinHandle;
+use crate::Instrumented;
+#[cfg(feature = "enable_profiling")]
+use crate::{Scope, ScopeKind, PROFILER};
+
+pub const WORKER_FRAME_KIND: &str = "tokio_worker";
+
+thread_local! {
+	#[cfg(feature = "enable_profiling")]
+	static PARK_SCOPE: RefCell<ThreadScope> = const { RefCell::new(ThreadScope(None)) };
+	#[cfg(all(feature = "enable_profiling", tokio_unstable))]
+	static POLL_SCOPE: RefCell<ThreadScope> = const { RefCell::new(ThreadScope(None)) };
+}
+
+// a scope that is still open when the thread exits, e.g. without `on_thread_stop`, ends with it
+#[cfg(feature = "enable_profiling")]
+struct ThreadScope(Option<Scope>);
+
+#[cfg(feature = "enable_profiling")]
+impl Drop for ThreadScope {
+	fn drop(&mut self) {
+		if let Some(scope) = self.0.take() {
+			scope.end_in_teardown();
+		}
+	}
+}
+
+const THREAD_NAME_PREFIX: &str = "tokio-runtime-";
+
+// replaces the thread name function of the builder with "tokio-runtime-{n}", the profiler lanes are named on thread start
+pub fn instrument_runtime(builder: &mut Builder) -> &mut Builder {
+	let thread_index = AtomicUsize::new(0);
+	builder
+		.thread_name_fn(move || format!("{THREAD_NAME_PREFIX}{}", thread_index.fetch_add(1, Ordering::Relaxed)))
+		.on_thread_start(on_thread_start)
+		.on_thread_park(on_thread_park)
+		.on_thread_unpark(on_thread_unpark)
+		.on_thread_stop(on_thread_stop);
+	#[cfg(tokio_unstable)]
+	builder
+		.on_before_task_poll(on_before_task_poll)
+		.on_after_task_poll(on_after_task_poll);
+	builder
+}
+
+// `tokio::spawn` with every poll of the task recorded
+pub fn spawn<S, F>(name: S, future: F) -> JoinHandle<F::Output>
+where
+	S: Into<String>,
+	F: Future + Send + 'static,
+	F::Output: Send + 'static,
+{
+	::tokio::spawn(Instrumented::new(name, future))
+}
+
+// the workers of a multi thread runtime are the first threads it starts, before it runs anything,
+// so the thread name tells them apart from the threads of the blocking pool
+#[cfg(feature = "enable_profiling")]
+fn lane_name() -> Option<String> {
+	let index: usize = std::thread::current().name()?.strip_prefix(THREAD_NAME_PREFIX)?.parse().ok()?;
+	let handle = Handle::try_current().ok()?;
+	let workers = match handle.runtime_flavor() {
+		RuntimeFlavor::MultiThread => handle.metrics().num_workers(),
+		_ => 0,
+	};
+	if index < workers {
+		Some(format!("tokio-worker-{index}"))
+	}
+	else {
+		Some(format!("tokio-blocking-{}", index - workers))
+	}
+}
+
+fn on_thread_start() {
+	#[cfg(feature = "enable_profiling")]
+	PROFILER.with_borrow_mut(|p| {
+		if let Some(name) = lane_name() {
+			p.set_thread_name(name);
+		}
+		p.auto_submit_frames(Some(WORKER_FRAME_KIND));
+	});
+}
+
+// a parked worker has run out of work, its frame is submitted by the auto submit interval once the park scope ends
+fn on_thread_park() {
+	#[cfg(feature = "enable_profiling")]
+	{
+		let scope = Scope::with_kind("tokio::park".to_string(), ScopeKind::Wait);
+		PARK_SCOPE.with_borrow_mut(|park_scope| park_scope.0 = Some(scope));
+	}
+}
+
+fn on_thread_unpark() {
+	#[cfg(feature = "enable_profiling")]
+	PARK_SCOPE.with_borrow_mut(|park_scope| park_scope.0.take());
+}
+
+fn on_thread_stop() {
+	#[cfg(feature = "enable_profiling")]
+	{
+		PARK_SCOPE.with_borrow_mut(|park_scope| park_scope.0.take());
+		PROFILER.with_borrow_mut(|p| p.flush_auto_submit_frame());
+	}
+}
+
+#[cfg(tokio_unstable)]
+fn on_before_task_poll(task: &::tokio::runtime::TaskMeta<'_>) {
+	#[cfg(feature = "enable_profiling")]
+	{
+		let scope = Scope::new(format!("tokio task {}", task.id()));
+		POLL_SCOPE.with_borrow_mut(|poll_scope| poll_scope.0 = Some(scope));
+	}
+}
+
+#[cfg(tokio_unstable)]
+fn on_after_task_poll(_task: &::tokio::runtime::TaskMeta<'_>) {
+	#[cfg(feature = "enable_profiling")]
+	POLL_SCOPE.with_borrow_mut(|poll_scope| poll_scope.0.take());
+}
@@ -0,0 +1,159 @@
+// A `tracing_subscriber::Layer` that records entered spans as scopes on the current thread,
+// with the span fields as scope metadata, and tracing events as `EventKind::Message` events.
+#[cfg(feature = "enable_profiling")]
+use std::cell::RefCell;
+#[cfg(feature = "enable_profiling")]
+use std::fmt::Debug;
+#[cfg(feature = "enable_profiling")]
+use ::tracing::field::{Field, Visit};
+use ::tracing::Subscriber;
+#[cfg(feature = "enable_profiling")]
+use ::tracing::{span, Event};
+#[cfg(feature = "enable_profiling")]
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use crate::Level;
+#[cfg(feature = "enable_profiling")]
+use crate::{EventKind, Scope, PROFILER};
+
+thread_local! {
+	// spans entered on this thread, innermost last
+	#[cfg(feature = "enable_profiling")]
+	static ENTERED_SPANS: RefCell<EnteredSpans> = const { RefCell::new(EnteredSpans(Vec::new())) };
+}
+
+// spans still entered when the thread exits end with it
+#[cfg(feature = "enable_profiling")]
+struct EnteredSpans(Vec<(span::Id, Scope)>);
+
+#[cfg(feature = "enable_profiling")]
+impl Drop for EnteredSpans {
+	fn drop(&mut self) {
+		while let Some((_, scope)) = self.0.pop() {
+			scope.end_in_teardown();
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProfilerLayer;
+
+impl ProfilerLayer {
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+pub fn level(level: &::tracing::Level) -> Level {
+	match *level {
+		::tracing::Level::ERROR => Level::Error,
+		::tracing::Level::WARN => Level::Warn,
+		::tracing::Level::INFO => Level::Info,
+		::tracing::Level::DEBUG => Level::Debug,
+		::tracing::Level::TRACE => Level::Trace,
+	}
+}
+
+// span and event fields, formatted with `Debug` unless they are strings
+#[derive(Debug, Default)]
+#[cfg(feature = "enable_profiling")]
+struct Fields(Vec<(String, String)>);
+
+#[cfg(feature = "enable_profiling")]
+impl Fields {
+	fn set(&mut self, key: &str, value: String) {
+		match self.0.iter_mut().find(|(existing, _)| existing == key) {
+			Some((_, existing)) => *existing = value,
+			None => self.0.push((key.to_string(), value)),
+		}
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+impl Visit for Fields {
+	fn record_str(&mut self, field: &Field, value: &str) {
+		self.set(field.name(), value.to_string());
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+		self.set(field.name(), format!("{value:?}"));
+	}
+}
+
+impl<S> Layer<S> for ProfilerLayer
+where S: Subscriber + for<'a> LookupSpan<'a>
+{
+	#[cfg(feature = "enable_profiling")]
+	fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+		let Some(span) = ctx.span(id) else {
+			return;
+		};
+		let mut fields = Fields::default();
+		attrs.record(&mut fields);
+		span.extensions_mut().insert(fields);
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+		let Some(span) = ctx.span(id) else {
+			return;
+		};
+		let mut extensions = span.extensions_mut();
+		if let Some(fields) = extensions.get_mut::<Fields>() {
+			values.record(fields);
+		}
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+		let Some(span) = ctx.span(id) else {
+			return;
+		};
+		let scope = Scope::new(format!("{}::{}", span.metadata().target(), span.metadata().name()));
+		ENTERED_SPANS.with_borrow_mut(|entered_spans| entered_spans.0.push((id.clone(), scope)));
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+		// spans can exit in any order, the scope of one that is not the innermost ends on its own
+		let scope = ENTERED_SPANS.with_borrow_mut(|entered_spans| {
+			let index = entered_spans.0.iter().rposition(|(entered, _)| entered == id)?;
+			Some(entered_spans.0.remove(index).1)
+		});
+		let Some(scope) = scope else {
+			return;
+		};
+		// the fields are added on exit, so that values recorded while the span was entered are included
+		if let Some(span) = ctx.span(id) {
+			let extensions = span.extensions();
+			if let Some(fields) = extensions.get::<Fields>() {
+				for (key, value) in fields.0.iter() {
+					scope.add_metadata(key.clone(), value.clone());
+				}
+			}
+		}
+		drop(scope);
+	}
+
+	#[cfg(feature = "enable_profiling")]
+	fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+		let mut fields = Fields::default();
+		event.record(&mut fields);
+		let name = match fields.0.iter().position(|(key, _)| key == "message") {
+			Some(index) => fields.0.remove(index).1,
+			None => event.metadata().name().to_string(),
+		};
+		let kind = EventKind::Message {
+			level: level(event.metadata().level()),
+			target: event.metadata().target().to_string(),
+			fields: fields.0,
+		};
+		// events from inside the profiler, e.g. a sink, or during thread teardown are dropped instead of panicking
+		let _ = PROFILER.try_with(|p| {
+			if let Ok(mut p) = p.try_borrow_mut() {
+				p.push_event(name, kind);
+			}
+		});
+	}
+}
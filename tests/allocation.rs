@@ -0,0 +1,40 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope};
+#[cfg(feature = "enable_profiling")]
+use profiler::allocation::thread_allocation_stats;
+use profiler::allocation::TrackingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn allocation_attribution_test() {
+	let before = thread_allocation_stats();
+	let bytes = std::hint::black_box(vec![0u8; 1000]);
+	drop(bytes);
+	let after = thread_allocation_stats();
+	assert!(after.allocated_bytes - before.allocated_bytes >= 1000);
+	assert!(after.deallocations > before.deallocations);
+
+	{
+		scope!("outer");
+		let _outer = std::hint::black_box(vec![0u8; 4096]);
+		{
+			scope!("inner");
+			let _inner = std::hint::black_box(vec![0u8; 1 << 20]);
+		}
+	}
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	let inner = frame.scope_results.iter().find(|s| s.name.ends_with("inner")).unwrap();
+	let outer = frame.scope_results.iter().find(|s| s.name.ends_with("outer")).unwrap();
+	assert!(inner.allocations.allocated_bytes >= 1 << 20);
+	assert!(inner.allocations.deallocated_bytes >= 1 << 20);
+	// the inner allocation belongs to the inner scope only
+	assert!(outer.allocations.allocated_bytes >= 4096);
+	assert!(outer.allocations.allocated_bytes < 1 << 20);
+	assert!(frame.allocations.allocated_bytes >= (1 << 20) + 4096);
+}
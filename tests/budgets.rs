@@ -0,0 +1,93 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, EventKind};
+#[cfg(feature = "enable_profiling")]
+use profiler::budget::{BudgetTarget, parse_budgets, set_budget, set_budget_violation_callback, budget_violation_counts};
+#[cfg(feature = "enable_profiling")]
+use profiler_attributes::profile;
+#[cfg(feature = "enable_profiling")]
+use std::{sync::{Arc, Mutex}, time::Duration};
+
+#[cfg(feature = "enable_profiling")]
+#[profile]
+fn slow() {
+	std::thread::sleep(Duration::from_millis(5));
+}
+
+#[cfg(feature = "enable_profiling")]
+#[profile]
+fn fast() {}
+
+#[test]
+fn parse_budgets_test() {
+	let budgets = profiler::budget::parse_budgets("
+		# render budgets
+		render::draw <= 4ms
+		frame <= 16.6ms
+		frame physics_tick <= 500us # fixed step
+	").unwrap();
+	assert_eq!(budgets.len(), 3);
+	assert_eq!(budgets[0].target, profiler::budget::BudgetTarget::Scope("render::draw".to_string()));
+	assert_eq!(budgets[0].limit, std::time::Duration::from_millis(4));
+	assert_eq!(budgets[1].target, profiler::budget::BudgetTarget::Frame(profiler::DEFAULT_FRAME_KIND.to_string()));
+	assert_eq!(budgets[1].limit, std::time::Duration::from_micros(16_600));
+	assert_eq!(budgets[2].target, profiler::budget::BudgetTarget::Frame("physics_tick".to_string()));
+
+	assert!(profiler::budget::parse_budgets("render::draw 4ms").is_err());
+	assert!(profiler::budget::parse_budgets("render::draw <= 4 parsecs").is_err());
+	let negative = profiler::budget::parse_budgets("# budgets\nrender::draw <= -1ms").unwrap_err();
+	assert!(negative.to_string().starts_with("line 2:"));
+	assert!(profiler::budget::parse_budgets("render::draw <= 1000000000000000000000000s").is_err());
+}
+
+#[test]
+fn quoted_budget_names_test() {
+	let budgets = profiler::budget::parse_budgets(r##"
+		"jobs (send)" <= 100us # a channel
+		"frame" <= 1ms
+		frame "fixed update" <= 2ms
+		"#1 <= 2" <= 3ms
+	"##).unwrap();
+	let targets: Vec<_> = budgets.iter().map(|budget| budget.target.clone()).collect();
+	assert_eq!(targets, vec![
+		profiler::budget::BudgetTarget::Scope("jobs (send)".to_string()),
+		profiler::budget::BudgetTarget::Scope("frame".to_string()),
+		profiler::budget::BudgetTarget::Frame("fixed update".to_string()),
+		profiler::budget::BudgetTarget::Scope("#1 <= 2".to_string()),
+	]);
+	assert_eq!(budgets[0].limit, std::time::Duration::from_micros(100));
+
+	assert!(profiler::budget::parse_budgets("jobs (send) <= 1ms").is_err());
+	assert!(profiler::budget::parse_budgets("\"jobs (send) <= 1ms").is_err());
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn budget_violations_test() {
+	for budget in parse_budgets("budgets::slow <= 1ms\nbudgets::fast <= 1ms\nframe tick <= 1ms").unwrap() {
+		set_budget(budget);
+	}
+	let violations = Arc::new(Mutex::new(Vec::new()));
+	let violations_clone = violations.clone();
+	set_budget_violation_callback(move |violation| violations_clone.lock().unwrap().push(violation.target.clone()));
+
+	for _ in 0..2 {
+		slow();
+		fast();
+		submit_frame!("tick");
+	}
+
+	let slow_target = BudgetTarget::Scope("budgets::slow".to_string());
+	let frame_target = BudgetTarget::Frame("tick".to_string());
+	assert_eq!(*violations.lock().unwrap(), vec![slow_target.clone(), frame_target.clone(), slow_target.clone(), frame_target.clone()]);
+	let counts = budget_violation_counts();
+	assert_eq!(counts.get(&slow_target), Some(&2));
+	assert_eq!(counts.get(&frame_target), Some(&2));
+	assert_eq!(counts.get(&BudgetTarget::Scope("budgets::fast".to_string())), None);
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	assert_eq!(frame.events.len(), 2);
+	assert_eq!(frame.events[0].name, "budgets::slow over budget");
+	assert!(matches!(frame.events[0].kind, EventKind::BudgetViolation { budget, duration } if budget == Duration::from_millis(1) && duration >= Duration::from_millis(5)));
+	assert_eq!(frame.events[1].name, "frame tick over budget");
+}
@@ -0,0 +1,88 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{EventKind, GLOBAL_PROFILER, submit_frame};
+#[cfg(feature = "enable_profiling")]
+use profiler::sync::mpsc::{channel, sync_channel, MAX_MESSAGE_EVENTS_PER_FRAME};
+#[cfg(feature = "enable_profiling")]
+use std::time::Duration;
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn queue_latency_test() {
+	let (sender, receiver) = channel("jobs");
+	sender.send(1).unwrap();
+	sender.send(2).unwrap();
+	std::thread::sleep(Duration::from_millis(20));
+	assert_eq!(receiver.recv().unwrap(), 1);
+	assert_eq!(receiver.try_recv().unwrap(), 2);
+
+	let producer = std::thread::spawn(move || {
+		std::thread::sleep(Duration::from_millis(20));
+		sender.send(3).unwrap();
+		submit_frame!();
+	});
+	assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![3]);
+	producer.join().unwrap();
+
+	let (sync_sender, sync_receiver) = sync_channel("results", 1);
+	sync_sender.send(4).unwrap();
+	assert!(sync_sender.try_send(5).is_err());
+	assert_eq!(sync_receiver.recv().unwrap(), 4);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let channel_statistics = global_profiler.channel_statistics();
+	let jobs = channel_statistics["jobs"];
+	assert_eq!(jobs.sent, 3);
+	assert_eq!(jobs.received, 3);
+	assert_eq!(jobs.max_queue_depth, 2);
+	assert!(jobs.max_latency >= Duration::from_millis(20));
+	let results = channel_statistics["results"];
+	assert_eq!(results.sent, 1);
+	assert_eq!(results.received, 1);
+
+	let frames: Vec<_> = global_profiler.thread_profilers.values()
+		.flat_map(|thread_profiler| thread_profiler.frames.iter())
+		.collect();
+	let queue_depths: Vec<u64> = frames.iter()
+		.flat_map(|frame| frame.events.iter())
+		.filter(|event| event.name == "jobs")
+		.map(|event| match event.kind {
+			EventKind::MessageReceived { queue_depth, .. } => queue_depth,
+			_ => panic!("unexpected event kind"),
+		})
+		.collect();
+	assert_eq!(queue_depths, vec![1, 0, 0]);
+	assert!(frames.iter()
+		.flat_map(|frame| frame.scope_results.iter())
+		.any(|scope_result| scope_result.name == "jobs (waiting)"));
+	// every send is a scope, also when it does not block
+	let sends: Vec<&str> = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "queue_latency_test")
+		.unwrap()
+		.frames.iter()
+		.flat_map(|frame| frame.scope_results.iter())
+		.map(|scope_result| scope_result.name.as_str())
+		.filter(|name| name.ends_with("(send)"))
+		.collect();
+	assert_eq!(sends, vec!["jobs (send)", "jobs (send)", "results (send)", "results (send)"]);
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn busy_channel_test() {
+	let (sender, receiver) = channel("busy");
+	for i in 0..MAX_MESSAGE_EVENTS_PER_FRAME * 2 {
+		sender.send(i).unwrap();
+		receiver.recv().unwrap();
+	}
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "busy_channel_test")
+		.unwrap()
+		.frames[0];
+	assert_eq!(frame.channels["busy"].received, MAX_MESSAGE_EVENTS_PER_FRAME * 2);
+	assert_eq!(frame.channels["busy"].max_queue_depth, 1);
+	assert_eq!(frame.events.len() as u64, MAX_MESSAGE_EVENTS_PER_FRAME);
+}
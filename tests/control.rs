@@ -0,0 +1,61 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope};
+#[cfg(feature = "enable_profiling")]
+use profiler::control::{is_recording, send_command, start_control_server, ControlCommand};
+
+#[cfg(feature = "enable_profiling")]
+fn record_frames(count: usize) {
+	for _ in 0..count {
+		{
+			scope!("work");
+		}
+		submit_frame!();
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+fn recorded_frames() -> usize {
+	GLOBAL_PROFILER.lock().unwrap().thread_profilers.values().map(|thread_profiler| thread_profiler.frames.len()).sum()
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn control_commands_test() {
+	let save_dir = std::env::temp_dir().join(format!("profiler_control_test_{}", std::process::id()));
+	std::fs::create_dir_all(&save_dir).unwrap();
+	let address = start_control_server("127.0.0.1:0", &save_dir).unwrap();
+	// the server doesn't wait for an idle client to disconnect
+	let _idle_client = std::net::TcpStream::connect(address).unwrap();
+	let send = |line: &str| send_command(address, &line.parse::<ControlCommand>().unwrap());
+
+	send("stop").unwrap();
+	record_frames(2);
+	assert_eq!(recorded_frames(), 0);
+
+	send("start").unwrap();
+	send("filter some other thread").unwrap();
+	record_frames(1);
+	assert_eq!(recorded_frames(), 0);
+	send("filter control").unwrap();
+	record_frames(1);
+	assert_eq!(recorded_frames(), 1);
+	send("filter").unwrap();
+
+	send("clear").unwrap();
+	send("capture 2").unwrap();
+	record_frames(3);
+	assert_eq!(recorded_frames(), 2);
+	assert!(!is_recording());
+
+	send("save capture.profiling").unwrap();
+	let saved = profiler::load_sessions_from_file(save_dir.join("capture.profiling")).unwrap();
+	std::fs::remove_dir_all(&save_dir).unwrap();
+	assert_eq!(saved[0].thread_profilers.values().next().unwrap().frames.len(), 2);
+
+	// only file names inside the save directory
+	assert!(send("save /tmp/file.profiling").is_err());
+	assert!(send("save ../file.profiling").is_err());
+	assert!("rewind".parse::<ControlCommand>().is_err());
+	assert!("capture many".parse::<ControlCommand>().is_err());
+	assert_eq!("filter  worker ".parse::<ControlCommand>().unwrap(), ControlCommand::Filter(Some("worker".to_string())));
+}
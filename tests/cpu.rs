@@ -0,0 +1,55 @@
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope, current_cpu};
+
+// the cpus this thread may run on
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+fn allowed_cpus() -> Vec<usize> {
+	unsafe {
+		let mut set: libc::cpu_set_t = std::mem::zeroed();
+		assert_eq!(libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set), 0);
+		(0..libc::CPU_SETSIZE as usize).filter(|cpu| libc::CPU_ISSET(*cpu, &set)).collect()
+	}
+}
+
+// moves the calling thread to `cpu` before it returns
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+fn pin_to_cpu(cpu: usize) {
+	unsafe {
+		let mut set: libc::cpu_set_t = std::mem::zeroed();
+		libc::CPU_SET(cpu, &mut set);
+		assert_eq!(libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set), 0);
+	}
+}
+
+#[test]
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+fn cpu_core_test() {
+	let cpus = allowed_cpus();
+	pin_to_cpu(cpus[0]);
+	assert_eq!(current_cpu(), Some(cpus[0] as u32));
+
+	{
+		scope!("pinned");
+		std::thread::sleep(std::time::Duration::from_millis(1));
+	}
+	// migrating needs a second cpu
+	let second_cpu = cpus.get(1).copied();
+	if let Some(second_cpu) = second_cpu {
+		scope!("migrating");
+		pin_to_cpu(second_cpu);
+	}
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let scope_results = &global_profiler.thread_profilers.values().next().unwrap().frames[0].scope_results;
+	let pinned = &scope_results[0];
+	assert_eq!(pinned.cpu_start, Some(cpus[0] as u32));
+	assert_eq!(pinned.cpu_end, Some(cpus[0] as u32));
+	assert!(!pinned.migrated());
+	if let Some(second_cpu) = second_cpu {
+		let migrating = &scope_results[1];
+		assert_eq!(migrating.cpu_start, Some(cpus[0] as u32));
+		assert_eq!(migrating.cpu_end, Some(second_cpu as u32));
+		assert!(migrating.migrated());
+	}
+}
@@ -0,0 +1,78 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, frame_label, scope, DEFAULT_FRAME_KIND};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn frame_kinds_test() {
+	for i in 0..3 {
+		{
+			scope!("physics");
+		}
+		submit_frame!("physics_tick");
+		{
+			scope!("render");
+		}
+		frame_label!(format!("render {i}"));
+		submit_frame!("render");
+	}
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let thread_profiler = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "frame_kinds_test")
+		.unwrap();
+	assert_eq!(thread_profiler.frames.len(), 7);
+
+	let physics_ids: Vec<u64> = thread_profiler.frames.iter().filter(|f| f.kind == "physics_tick").map(|f| f.id).collect();
+	assert_eq!(physics_ids, vec![0, 1, 2]);
+	let render_frames: Vec<&profiler::Frame> = thread_profiler.frames.iter().filter(|f| f.kind == "render").collect();
+	assert_eq!(render_frames.len(), 3);
+	assert_eq!(render_frames[2].id, 2);
+	assert_eq!(render_frames[2].label.as_deref(), Some("render 2"));
+	assert_eq!(render_frames[2].scope_results.len(), 1);
+	assert_eq!(render_frames[2].scope_results[0].name, "frames::frame_kinds_test::render");
+	assert_eq!(thread_profiler.frames.iter().filter(|f| f.kind == DEFAULT_FRAME_KIND).count(), 1);
+
+	let statistics = global_profiler.frame_statistics();
+	let kinds: Vec<&str> = statistics.iter().map(|s| s.kind.as_str()).collect();
+	assert!(kinds.contains(&DEFAULT_FRAME_KIND));
+	assert!(kinds.windows(2).all(|pair| pair[0] < pair[1]));
+	let render = statistics.iter().find(|s| s.kind == "render").unwrap();
+	assert_eq!(render.count, 3);
+	assert!(render.min <= render.average() && render.average() <= render.max);
+}
+
+// kinds share the timeline of the thread, the draw frame only covers the time since the last tick
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn interleaved_frame_kinds_test() {
+	{
+		scope!("render setup");
+	}
+	for _ in 0..2 {
+		{
+			scope!("physics");
+		}
+		submit_frame!("tick");
+	}
+	{
+		scope!("render");
+	}
+	submit_frame!("draw");
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frames = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "interleaved_frame_kinds_test")
+		.unwrap()
+		.frames;
+	let kinds: Vec<&str> = frames.iter().map(|frame| frame.kind.as_str()).collect();
+	assert_eq!(kinds, vec!["tick", "tick", "draw"]);
+	for pair in frames.windows(2) {
+		assert!(pair[0].start + pair[0].duration <= pair[1].start);
+	}
+	// the scope before the first tick went into the tick's frame
+	let scope_names: Vec<&str> = frames[0].scope_results.iter().map(|scope_result| scope_result.name.as_str()).collect();
+	assert_eq!(scope_names, vec!["frames::interleaved_frame_kinds_test::render setup", "frames::interleaved_frame_kinds_test::physics"]);
+	assert_eq!(frames[2].scope_results.len(), 1);
+	assert_eq!(frames[2].scope_results[0].name, "frames::interleaved_frame_kinds_test::render");
+}
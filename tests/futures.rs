@@ -0,0 +1,88 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, InstrumentFuture, submit_frame};
+#[cfg(feature = "enable_profiling")]
+use profiler_attributes::profile;
+#[cfg(feature = "enable_profiling")]
+use std::{future::Future, pin::Pin, task::{Context, Poll, Waker}, time::Duration};
+
+// pending until it was polled `remaining` more times
+#[cfg(feature = "enable_profiling")]
+struct YieldTimes {
+	remaining: usize,
+}
+
+#[cfg(feature = "enable_profiling")]
+impl Future for YieldTimes {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+		if self.remaining == 0 {
+			return Poll::Ready(());
+		}
+		self.remaining -= 1;
+		Poll::Pending
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+#[profile]
+async fn load(value: &u32) -> Result<u32, String> {
+	YieldTimes { remaining: 1 }.await;
+	let parsed: u32 = value.to_string().parse().map_err(|_| "not a number".to_string())?;
+	Ok(parsed * 2)
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn polls_across_threads_test() {
+	let mut cx = Context::from_waker(Waker::noop());
+
+	let mut task = Box::pin(async {
+		YieldTimes { remaining: 1 }.await;
+		std::thread::sleep(Duration::from_millis(10));
+	}.instrumented("task"));
+	assert!(task.as_mut().poll(&mut cx).is_pending());
+	submit_frame!();
+	std::thread::sleep(Duration::from_millis(20));
+
+	// the second poll runs on another thread
+	std::thread::spawn(move || {
+		let mut cx = Context::from_waker(Waker::noop());
+		assert!(task.as_mut().poll(&mut cx).is_ready());
+		submit_frame!();
+	}).join().unwrap();
+
+	let value = 21;
+	let mut load = Box::pin(load(&value));
+	assert!(load.as_mut().poll(&mut cx).is_pending());
+	assert_eq!(load.as_mut().poll(&mut cx), Poll::Ready(Ok(42)));
+
+	let mut dropped = Box::pin(YieldTimes { remaining: 5 }.instrumented("dropped"));
+	assert!(dropped.as_mut().poll(&mut cx).is_pending());
+	drop(dropped);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let task_spans = global_profiler.task_spans();
+	assert_eq!(task_spans.len(), 3);
+	let task = task_spans.iter().find(|task_span| task_span.name == "task").unwrap();
+	assert_eq!(task.polls, 2);
+	assert!(task.completed);
+	assert!(task.duration >= Duration::from_millis(30));
+	assert!(task.busy >= Duration::from_millis(10));
+	assert!(task.busy < task.duration);
+	let load = task_spans.iter().find(|task_span| task_span.name == "futures::load").unwrap();
+	assert_eq!(load.polls, 2);
+	assert!(load.completed);
+	let dropped = task_spans.iter().find(|task_span| task_span.name == "dropped").unwrap();
+	assert_eq!(dropped.polls, 1);
+	assert!(!dropped.completed);
+
+	// one poll slice on each thread
+	let threads_with_task_slices = global_profiler.thread_profilers.values()
+		.filter(|thread_profiler| thread_profiler.frames.iter()
+			.flat_map(|frame| frame.scope_results.iter())
+			.any(|scope_result| scope_result.task == Some(task.id)))
+		.count();
+	assert_eq!(threads_with_task_slices, 2);
+}
@@ -0,0 +1,39 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope, define_global_frames, current_global_frame};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn global_frames_test() {
+	assert_eq!(current_global_frame(), None);
+	define_global_frames!();
+	assert_eq!(current_global_frame(), Some(0));
+
+	for _ in 0..2 {
+		{
+			scope!("main loop");
+			// finishes before the global frame ends, so its work belongs to the current global frame
+			std::thread::Builder::new().name("worker".to_string()).spawn(|| {
+				{
+					scope!("worker");
+				}
+				submit_frame!();
+			}).unwrap().join().unwrap();
+		}
+		submit_frame!();
+	}
+	assert_eq!(current_global_frame(), Some(2));
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let global_frames = global_profiler.global_frames();
+	assert_eq!(global_frames.iter().map(|f| f.id).collect::<Vec<u64>>(), vec![0, 1]);
+
+	let main_thread = global_profiler.thread_profilers.values().find(|t| t.defines_global_frames).unwrap();
+	assert_eq!(main_thread.frames[1].scope_results[0].global_frame, Some(1));
+
+	let mut worker_global_frames: Vec<Option<u64>> = global_profiler.thread_profilers.values()
+		.filter(|t| t.name == "worker")
+		.map(|t| t.frames[0].scope_results[0].global_frame)
+		.collect();
+	worker_global_frames.sort();
+	assert_eq!(worker_global_frames, vec![Some(0), Some(1)]);
+}
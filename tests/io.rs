@@ -0,0 +1,45 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame};
+#[cfg(feature = "enable_profiling")]
+use profiler::io::Profiled;
+#[cfg(feature = "enable_profiling")]
+use std::io::{Read, Write};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn io_statistics_test() {
+	let path = std::env::temp_dir().join(format!("profiler_io_test_{}.bin", std::process::id()));
+	{
+		let mut file = profiler::io::create(&path).unwrap();
+		file.write_all(&[1; 1000]).unwrap();
+		file.flush().unwrap();
+	}
+	let mut data = Vec::new();
+	profiler::io::open(&path).unwrap().read_to_end(&mut data).unwrap();
+	assert_eq!(data.len(), 1000);
+	std::fs::remove_file(&path).unwrap();
+
+	let mut reader = Profiled::new("memory", [2u8; 10].as_slice());
+	let mut buffer = [0; 4];
+	assert_eq!(reader.read(&mut buffer).unwrap(), 4);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let io_statistics = global_profiler.io_statistics();
+	let file = io_statistics[&path.display().to_string()];
+	assert_eq!(file.bytes_written, 1000);
+	assert_eq!(file.bytes_read, 1000);
+	assert_eq!(file.flushes, 1);
+	assert!(file.writes >= 1);
+	// read_to_end reads until it gets 0 bytes back
+	assert!(file.reads >= 2);
+	let memory = io_statistics["memory"];
+	assert_eq!(memory.reads, 1);
+	assert_eq!(memory.bytes_read, 4);
+
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	let memory_read = frame.scope_results.iter().find(|scope_result| scope_result.name == "memory (read)").unwrap();
+	assert_eq!(memory_read.bytes, Some(4));
+	let flush = frame.scope_results.iter().find(|scope_result| scope_result.name.ends_with("(flush)")).unwrap();
+	assert_eq!(flush.bytes, None);
+}
@@ -0,0 +1,59 @@
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+use profiler::{EventKind, GLOBAL_PROFILER, Level, PROFILER, submit_frame};
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+use profiler::log::ProfilerLogger;
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+use log::{LevelFilter, Log, Metadata, Record};
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+static FORWARDED: AtomicUsize = AtomicUsize::new(0);
+
+// only interested in warnings and errors
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+struct CountingLogger;
+
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+impl Log for CountingLogger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		metadata.level() <= log::Level::Warn
+	}
+
+	fn log(&self, _record: &Record) {
+		FORWARDED.fetch_add(1, Ordering::Relaxed);
+	}
+
+	fn flush(&self) {}
+}
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "log"))]
+fn log_records_test() {
+	ProfilerLogger::new(CountingLogger).init(LevelFilter::Debug).unwrap();
+
+	log::warn!(target: "assets", "texture {} missing", 7);
+	log::debug!("loading");
+	log::trace!("filtered out by the max level");
+	// e.g. logging from a sink, the record is dropped instead of panicking
+	PROFILER.with_borrow_mut(|_| log::info!("while the profiler is borrowed"));
+	submit_frame!();
+
+	assert_eq!(FORWARDED.load(Ordering::Relaxed), 1);
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	assert_eq!(frame.events.len(), 2);
+	assert_eq!(frame.events[0].name, "texture 7 missing");
+	match &frame.events[0].kind {
+		EventKind::Message { level, target, fields } => {
+			assert_eq!(*level, Level::Warn);
+			assert_eq!(target, "assets");
+			assert_eq!(fields[0].0, "location");
+			assert!(fields[0].1.starts_with("tests/log.rs:"));
+		},
+		kind => panic!("unexpected event kind {kind:?}"),
+	}
+	assert_eq!(frame.events[1].name, "loading");
+	assert!(matches!(frame.events[1].kind, EventKind::Message { level: Level::Debug, .. }));
+}
@@ -0,0 +1,64 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope};
+#[cfg(feature = "enable_profiling")]
+use profiler::perf_counters::{enable_perf_counters, thread_perf_counters};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn perf_counters_fallback_test() {
+	{
+		scope!("disabled");
+	}
+	enable_perf_counters(true);
+	let available = thread_perf_counters().is_some();
+	{
+		scope!("enabled");
+		let mut sum = 0u64;
+		for i in 0..100_000u64 {
+			sum = sum.wrapping_add(std::hint::black_box(i));
+		}
+		std::hint::black_box(sum);
+	}
+	enable_perf_counters(false);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "perf_counters_fallback_test")
+		.unwrap()
+		.frames[0];
+	assert_eq!(frame.scope_results[0].perf_counters, None);
+	// without perf event access (e.g. in containers) the scope is still recorded, just without counters
+	assert_eq!(frame.scope_results[1].perf_counters.is_some(), available);
+	if let Some(perf_counters) = frame.scope_results[1].perf_counters {
+		if let Some(instructions) = perf_counters.instructions {
+			assert!(instructions >= 100_000);
+		}
+	}
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn context_switches_test() {
+	enable_perf_counters(true);
+	let available = thread_perf_counters().is_some();
+	{
+		scope!("sleeping");
+		// sleeping always switches to another task
+		std::thread::sleep(std::time::Duration::from_millis(5));
+	}
+	enable_perf_counters(false);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "context_switches_test")
+		.unwrap()
+		.frames[0];
+	let perf_counters = frame.scope_results[0].perf_counters;
+	assert_eq!(perf_counters.is_some(), available);
+	// only the software counters count context switches, they are opened when the hardware ones can't be
+	if let Some(context_switches) = perf_counters.and_then(|perf_counters| perf_counters.context_switches) {
+		assert!(context_switches > 0);
+	}
+}
@@ -0,0 +1,61 @@
+#[cfg(feature = "enable_profiling")]
+use std::process::Command;
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, scope, submit_frame};
+#[cfg(feature = "enable_profiling")]
+use profiler::process::{merge_child_sessions, save_to_parent_session, CommandExt};
+
+// only set for the copy of this test binary that `merge_child_sessions_test` starts
+#[cfg(feature = "enable_profiling")]
+const CHILD_VAR: &str = "PROFILER_PROCESS_TEST_CHILD";
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn child_process() {
+	if std::env::var_os(CHILD_VAR).is_none() {
+		return;
+	}
+	{
+		scope!("child work");
+	}
+	submit_frame!();
+	assert!(save_to_parent_session().unwrap());
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn merge_child_sessions_test() {
+	let dir = std::env::temp_dir().join(format!("profiler_process_test_{}", std::process::id()));
+	{
+		scope!("parent work");
+		let output = Command::new(std::env::current_exe().unwrap())
+			.args(["child_process", "--exact"])
+			.env(CHILD_VAR, "1")
+			.share_session(&dir)
+			.unwrap()
+			.output()
+			.unwrap();
+		assert!(output.status.success());
+	}
+	submit_frame!();
+	assert_eq!(merge_child_sessions(&dir).unwrap(), 1);
+	std::fs::remove_dir_all(&dir).unwrap();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	assert_eq!(global_profiler.processes.len(), 2);
+	// only the child joined the session
+	assert!(profiler::process::parent_session_dir().is_none());
+	let parent_scope = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "merge_child_sessions_test")
+		.map(|thread_profiler| &thread_profiler.frames[0].scope_results[0])
+		.unwrap();
+	let child_thread = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.process != std::process::id())
+		.unwrap();
+	assert!(global_profiler.processes.contains_key(&child_thread.process));
+	assert_eq!(child_thread.name, "child_process");
+	let child_scope = &child_thread.frames[0].scope_results[0];
+	assert!(child_scope.name.ends_with("child work"));
+	// the child ran while the parent waited for it
+	assert!(child_scope.is_inside(parent_scope));
+}
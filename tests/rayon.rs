@@ -0,0 +1,81 @@
+#[cfg(all(feature = "enable_profiling", feature = "rayon"))]
+use profiler::{GLOBAL_PROFILER, ScopeParent, scope, submit_frame};
+#[cfg(all(feature = "enable_profiling", feature = "rayon"))]
+use profiler::rayon::{in_parent, instrument_thread_pool, submit_worker_frames, WORKER_FRAME_KIND};
+#[cfg(all(feature = "enable_profiling", feature = "rayon"))]
+use rayon::prelude::*;
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "rayon"))]
+fn pool_jobs_attributed_to_caller_test() {
+	let pool = instrument_thread_pool(rayon::ThreadPoolBuilder::new())
+		.num_threads(2)
+		.build()
+		.unwrap();
+	{
+		scope!("caller");
+		let work = in_parent(|i: u64| {
+			scope!("job");
+			i * 2
+		});
+		let total: u64 = pool.install(|| (0..16u64).into_par_iter().map(&work).sum());
+		assert_eq!(total, 240);
+	}
+	pool.install(submit_worker_frames);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let caller_thread = *global_profiler.thread_profilers.iter()
+		.find(|(_, thread_profiler)| thread_profiler.name == "pool_jobs_attributed_to_caller_test")
+		.unwrap().0;
+	let workers: Vec<_> = global_profiler.thread_profilers.values()
+		.filter(|thread_profiler| thread_profiler.name.starts_with("rayon-worker-"))
+		.collect();
+	assert!(!workers.is_empty());
+	// workers of the pool of the other test have the same names
+	let worker_frames: Vec<_> = workers.iter()
+		.flat_map(|worker| worker.frames.iter())
+		.inspect(|frame| assert_eq!(frame.kind, WORKER_FRAME_KIND))
+		.filter(|frame| frame.scope_results.iter().any(|scope_result| scope_result.name.ends_with("::job")))
+		.collect();
+	let jobs: Vec<_> = worker_frames.iter()
+		.flat_map(|frame| frame.scope_results.iter())
+		.collect();
+	// the jobs are batched into a few frames per worker
+	assert_eq!(jobs.len(), 16);
+	assert!(worker_frames.len() < jobs.len());
+	assert!(jobs.iter().all(|job| job.name.ends_with("::job")));
+	assert!(jobs.iter().all(|job| job.parent == Some(ScopeParent { thread: caller_thread, depth: 0 })));
+
+	let caller = &global_profiler.thread_profilers[&caller_thread].frames[0].scope_results[0];
+	assert!(caller.name.ends_with("::caller"));
+	assert_eq!(caller.parent, None);
+	assert!(jobs.iter().all(|job| job.is_inside(caller)));
+}
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "rayon"))]
+fn panicking_job_restores_parent_test() {
+	let pool = instrument_thread_pool(rayon::ThreadPoolBuilder::new())
+		.num_threads(1)
+		.build()
+		.unwrap();
+	{
+		scope!("caller");
+		let work = in_parent(|_: ()| panic!("job failed"));
+		let result = pool.install(|| std::panic::catch_unwind(|| work(())));
+		assert!(result.is_err());
+	}
+	pool.install(|| {
+		scope!("after");
+	});
+	pool.install(submit_worker_frames);
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let after = global_profiler.thread_profilers.values()
+		.flat_map(|thread_profiler| thread_profiler.frames.iter())
+		.flat_map(|frame| frame.scope_results.iter())
+		.find(|scope_result| scope_result.name.ends_with("::after"))
+		.unwrap();
+	assert_eq!(after.parent, None);
+}
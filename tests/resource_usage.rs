@@ -0,0 +1,39 @@
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope};
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+use profiler::resource_usage::{enable_resource_usage_sampling, thread_resource_usage};
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+use std::time::Duration;
+
+#[test]
+#[cfg(all(feature = "enable_profiling", target_os = "linux"))]
+fn cpu_time_vs_wall_time_test() {
+	{
+		scope!("not sampled");
+	}
+
+	enable_resource_usage_sampling(true);
+	{
+		scope!("sleeping");
+		std::thread::sleep(Duration::from_millis(50));
+	}
+	{
+		scope!("spinning");
+		// spins on cpu time, so that being preempted on a busy machine doesn't matter
+		let start = thread_resource_usage().unwrap().cpu_time;
+		while thread_resource_usage().unwrap().cpu_time - start < Duration::from_millis(50) {
+			std::hint::spin_loop();
+		}
+	}
+	enable_resource_usage_sampling(false);
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	assert_eq!(frame.scope_results[0].resource_usage, None);
+	let sleeping = frame.scope_results[1].resource_usage.unwrap();
+	let spinning = frame.scope_results[2].resource_usage.unwrap();
+	assert!(sleeping.cpu_time < Duration::from_millis(25));
+	assert!(sleeping.voluntary_context_switches >= 1);
+	assert!(spinning.cpu_time >= Duration::from_millis(25));
+}
@@ -0,0 +1,56 @@
+#[cfg(all(feature = "enable_profiling", feature = "sampling", target_os = "linux"))]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope};
+#[cfg(all(feature = "enable_profiling", feature = "sampling", target_os = "linux"))]
+use profiler::sampling::{register_current_thread, start_sampling, stop_sampling};
+#[cfg(all(feature = "enable_profiling", feature = "sampling", target_os = "linux"))]
+use std::time::Duration;
+
+#[cfg(all(feature = "enable_profiling", feature = "sampling", target_os = "linux"))]
+fn thread_cpu_time() -> Duration {
+	let mut time: libc::timespec = unsafe { std::mem::zeroed() };
+	unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+	Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+// runs for a while on the cpu rather than the wall clock, so the thread can be sampled even on a busy machine
+#[cfg(all(feature = "enable_profiling", feature = "sampling", target_os = "linux"))]
+#[inline(never)]
+fn uninstrumented_hot_loop() {
+	let start = thread_cpu_time();
+	let mut value = 0u64;
+	while thread_cpu_time() - start < Duration::from_millis(100) {
+		for _ in 0..100_000 {
+			value = std::hint::black_box(value.wrapping_mul(6364136223846793005).wrapping_add(1));
+		}
+	}
+}
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "sampling", target_os = "linux"))]
+fn sampling_test() {
+	// the action sampling replaces is restored when it stops
+	unsafe { libc::signal(libc::SIGPROF, libc::SIG_IGN) };
+	register_current_thread();
+	start_sampling(Duration::from_millis(1));
+	{
+		scope!("big scope");
+		uninstrumented_hot_loop();
+	}
+	stop_sampling();
+	let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+	unsafe { libc::sigaction(libc::SIGPROF, std::ptr::null(), &mut action) };
+	assert_eq!(action.sa_sigaction, libc::SIG_IGN);
+	submit_frame!();
+
+	let mut global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	// samples hold raw addresses until they are symbolised, e.g. when they are saved
+	assert!(global_profiler.thread_profilers.values()
+		.flat_map(|thread_profiler| thread_profiler.frames.iter())
+		.flat_map(|frame| frame.samples.iter())
+		.all(|sample| !sample.addresses.is_empty() && sample.stack.is_empty()));
+	global_profiler.symbolize_samples();
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	let big_scope = &frame.scope_results[0];
+	assert!(frame.samples.iter()
+		.any(|sample| sample.time >= big_scope.start && sample.time <= big_scope.start + big_scope.duration));
+}
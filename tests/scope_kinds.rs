@@ -0,0 +1,46 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, ScopeKind, submit_frame, scope, wait_scope};
+#[cfg(feature = "enable_profiling")]
+use std::time::{Duration, Instant};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn busy_and_blocked_time_test() {
+	{
+		scope!("frame");
+		{
+			scope!("spinning");
+			let start = Instant::now();
+			while start.elapsed() < Duration::from_millis(30) {
+				std::hint::spin_loop();
+			}
+		}
+		{
+			wait_scope!("waiting for input");
+			// nested blocked scopes are only counted once
+			profiler::sleep(Duration::from_millis(30));
+		}
+		{
+			wait_scope!("loading", ScopeKind::Io);
+			std::thread::sleep(Duration::from_millis(10));
+		}
+	}
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values().next().unwrap().frames[0];
+	let kind_of = |suffix: &str| frame.scope_results.iter().find(|scope_result| scope_result.name.ends_with(suffix)).unwrap().kind;
+	assert_eq!(kind_of("spinning"), ScopeKind::Cpu);
+	assert_eq!(kind_of("waiting for input"), ScopeKind::Wait);
+	assert_eq!(kind_of("std::thread::sleep"), ScopeKind::Sleep);
+	assert_eq!(kind_of("loading"), ScopeKind::Io);
+
+	let blocked = frame.blocked_time();
+	assert!(blocked >= Duration::from_millis(40));
+	assert!(blocked < Duration::from_millis(100));
+	assert!(frame.busy_time() >= Duration::from_millis(30));
+
+	let statistics = &global_profiler.frame_statistics()[0];
+	assert_eq!(statistics.average_blocked(), blocked);
+	assert_eq!(statistics.average_busy(), frame.duration - blocked);
+}
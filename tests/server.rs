@@ -0,0 +1,55 @@
+#[cfg(feature = "enable_profiling")]
+use std::net::TcpStream;
+#[cfg(feature = "enable_profiling")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "enable_profiling")]
+use std::sync::Arc;
+#[cfg(feature = "enable_profiling")]
+use std::time::Duration;
+#[cfg(feature = "enable_profiling")]
+use profiler::{remove_sink, scope, submit_frame};
+#[cfg(feature = "enable_profiling")]
+use profiler::server::start_server;
+#[cfg(feature = "enable_profiling")]
+use profiler::stream::{read_message, StreamMessage, MAX_MESSAGE_LENGTH};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn stream_frames_over_tcp_test() {
+	let (sink, address) = start_server("127.0.0.1:0").unwrap();
+	let mut stream = TcpStream::connect(address).unwrap();
+	stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+
+	// frames submitted before the server accepted the connection are not sent
+	let running = Arc::new(AtomicBool::new(true));
+	let app_running = running.clone();
+	let app = std::thread::Builder::new().name("app".to_string()).spawn(move || {
+		while app_running.load(Ordering::Relaxed) {
+			{
+				scope!("update");
+			}
+			submit_frame!();
+			std::thread::sleep(Duration::from_millis(5));
+		}
+	}).unwrap();
+
+	let StreamMessage::Frame { thread, frame } = read_message(&mut stream).unwrap().unwrap();
+	assert_eq!(thread.name, "app");
+	assert!(frame.scope_results[0].name.ends_with("update"));
+	running.store(false, Ordering::Relaxed);
+	app.join().unwrap();
+
+	// the server closes the connection and the listener once its sink is removed
+	drop(remove_sink(sink));
+	while read_message(&mut stream).unwrap().is_some() {}
+	let (sink, _) = start_server(address).unwrap();
+	drop(remove_sink(sink));
+	assert!(TcpStream::connect(address).is_err());
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn message_length_limit_test() {
+	let length = (MAX_MESSAGE_LENGTH as u32 + 1).to_le_bytes();
+	assert!(read_message(&mut &length[..]).is_err());
+}
@@ -0,0 +1,86 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, start_session, end_session, reset, sessions_to_binary, sessions_from_binary};
+#[cfg(feature = "enable_profiling")]
+use profiler_attributes::profile;
+
+// the tests replace and clear the recording of the whole process
+#[cfg(feature = "enable_profiling")]
+static SESSIONS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(feature = "enable_profiling")]
+#[profile]
+fn work() {
+	std::thread::sleep(std::time::Duration::from_millis(1));
+}
+
+#[cfg(feature = "enable_profiling")]
+fn record_frames(count: usize) {
+	for _ in 0..count {
+		work();
+		submit_frame!();
+	}
+}
+
+#[cfg(feature = "enable_profiling")]
+fn frame_count(session: &profiler::GlobalProfiler) -> usize {
+	session.thread_profilers.values().map(|t| t.frames.len()).sum()
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn sessions_test() {
+	let _sessions = SESSIONS.lock().unwrap();
+	record_frames(3);
+	reset();
+	assert_eq!(frame_count(&GLOBAL_PROFILER.lock().unwrap()), 0);
+
+	start_session("startup").unwrap();
+	record_frames(2);
+	let startup = end_session();
+	assert_eq!(startup.session_name, "startup");
+	assert_eq!(frame_count(&startup), 2);
+
+	start_session("level 1").unwrap();
+	assert!(start_session("level 2").is_err());
+	record_frames(5);
+	let level_1 = end_session();
+	assert_eq!(level_1.session_name, "level 1");
+	assert_eq!(frame_count(&level_1), 5);
+	assert_eq!(frame_count(&GLOBAL_PROFILER.lock().unwrap()), 0);
+
+	let bytes = sessions_to_binary(&[startup, level_1]).expect("failed to generate binary from sessions");
+	let sessions = sessions_from_binary(&bytes).expect("failed to parse binary for sessions");
+	assert_eq!(sessions.len(), 2);
+	assert_eq!(sessions[0].session_name, "startup");
+	assert_eq!(sessions[1].session_name, "level 1");
+	assert_eq!(frame_count(&sessions[1]), 5);
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn stale_frames_dropped_test() {
+	let _sessions = SESSIONS.lock().unwrap();
+	let (started_sender, started_receiver) = std::sync::mpsc::channel();
+	let (reset_sender, reset_receiver) = std::sync::mpsc::channel();
+	let worker = std::thread::Builder::new().name("stale frame".to_string()).spawn(move || {
+		work();
+		started_sender.send(()).unwrap();
+		reset_receiver.recv().unwrap();
+		// started before the session was reset, so it is dropped
+		submit_frame!();
+		work();
+		submit_frame!();
+	}).unwrap();
+	started_receiver.recv().unwrap();
+	reset();
+	reset_sender.send(()).unwrap();
+	worker.join().unwrap();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frames = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "stale frame")
+		.unwrap()
+		.frames;
+	assert_eq!(frames.len(), 1);
+	assert_eq!(frames[0].id, 1);
+}
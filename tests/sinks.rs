@@ -0,0 +1,41 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame, scope, add_sink, remove_sink, SinkId, CallbackSink, FileStreamSink};
+#[cfg(feature = "enable_profiling")]
+use std::sync::{Arc, Mutex};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn sinks_test() {
+	let frames = Arc::new(Mutex::new(Vec::new()));
+	let scope_names = Arc::new(Mutex::new(Vec::new()));
+	let frames_clone = frames.clone();
+	let scope_names_clone = scope_names.clone();
+	let callback_sink = add_sink(CallbackSink::new(move |thread, frame| frames_clone.lock().unwrap().push((thread.name.clone(), frame.id)))
+		.with_scope_result_callback(move |_, scope_result| scope_names_clone.lock().unwrap().push(scope_result.name.clone())));
+
+	let stream_path = std::env::temp_dir().join(format!("profiler_sinks_test_{}.profiling", std::process::id()));
+	let file_stream_sink = add_sink(FileStreamSink::create(&stream_path).unwrap());
+	assert!(remove_sink(SinkId::MEMORY).is_some(), "memory sink should be registered by default");
+
+	for _ in 0..3 {
+		{
+			scope!("work");
+		}
+		submit_frame!();
+	}
+
+	assert!(GLOBAL_PROFILER.lock().unwrap().thread_profilers.is_empty());
+	assert_eq!(frames.lock().unwrap().iter().map(|(_, id)| *id).collect::<Vec<u64>>(), vec![0, 1, 2]);
+	assert_eq!(frames.lock().unwrap()[0].0, "sinks_test");
+	assert_eq!(*scope_names.lock().unwrap(), vec!["sinks::sinks_test::work"; 3]);
+
+	drop(remove_sink(file_stream_sink));
+	let streamed = profiler::stream::load_stream_from_file(&stream_path).unwrap();
+	std::fs::remove_file(&stream_path).unwrap();
+	let thread_profiler = streamed.thread_profilers.values().next().unwrap();
+	assert_eq!(thread_profiler.frames.len(), 3);
+	assert_eq!(thread_profiler.frames[2].scope_results[0].name, "sinks::sinks_test::work");
+
+	assert!(remove_sink(callback_sink).is_some());
+	assert!(remove_sink(callback_sink).is_none());
+}
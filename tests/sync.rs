@@ -0,0 +1,101 @@
+#[cfg(feature = "enable_profiling")]
+use profiler::{GLOBAL_PROFILER, submit_frame};
+#[cfg(feature = "enable_profiling")]
+use profiler::sync::{Mutex, RwLock};
+#[cfg(feature = "enable_profiling")]
+use std::{sync::{Arc, Barrier}, time::Duration};
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn lock_contention_test() {
+	let mutex = Arc::new(Mutex::new("shared state", 0));
+	let barrier = Arc::new(Barrier::new(2));
+	let holder = {
+		let mutex = mutex.clone();
+		let barrier = barrier.clone();
+		std::thread::spawn(move || {
+			let mut guard = mutex.lock().unwrap();
+			barrier.wait();
+			std::thread::sleep(Duration::from_millis(50));
+			*guard += 1;
+			drop(guard);
+			submit_frame!();
+		})
+	};
+	barrier.wait();
+	*mutex.lock().unwrap() += 1;
+	holder.join().unwrap();
+
+	let rw_lock = RwLock::new("config", 1);
+	assert_eq!(*rw_lock.read().unwrap(), 1);
+	*rw_lock.write().unwrap() = 2;
+	submit_frame!();
+
+	assert_eq!(*mutex.lock().unwrap(), 2);
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let lock_contention = global_profiler.lock_contention();
+	let shared_state = lock_contention["shared state"];
+	assert_eq!(shared_state.acquisitions, 2);
+	assert_eq!(shared_state.contended_acquisitions, 1);
+	assert!(shared_state.max_wait >= Duration::from_millis(25));
+	assert!(shared_state.total_held >= Duration::from_millis(50));
+	let config = lock_contention["config"];
+	assert_eq!(config.acquisitions, 2);
+	assert_eq!(config.contended_acquisitions, 0);
+
+	let scope_names: Vec<&str> = global_profiler.thread_profilers.values()
+		.flat_map(|thread_profiler| thread_profiler.frames.iter())
+		.flat_map(|frame| frame.scope_results.iter())
+		.map(|scope_result| scope_result.name.as_str())
+		.collect();
+	assert!(scope_names.contains(&"shared state (waiting)"));
+	assert!(scope_names.contains(&"shared state (locked)"));
+	assert!(scope_names.contains(&"config (read locked)"));
+	assert!(scope_names.contains(&"config (write locked)"));
+}
+
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn out_of_order_release_test() {
+	std::thread::Builder::new().name("out of order release".to_string()).spawn(|| {
+		let first = Mutex::new("first", 0);
+		let second = Mutex::new("second", 0);
+		let first_guard = first.lock().unwrap();
+		let second_guard = second.lock().unwrap();
+		drop(first_guard);
+		let third = Mutex::new("third", 0);
+		let third_guard = third.lock().unwrap();
+		drop(third_guard);
+		drop(second_guard);
+		submit_frame!();
+	}).unwrap().join().unwrap();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let thread_profiler = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "out of order release")
+		.unwrap();
+	let scope_results = &thread_profiler.frames[0].scope_results;
+	let depth = |name: &str| scope_results.iter().find(|scope_result| scope_result.name == name).unwrap().depth;
+	assert_eq!(depth("first (locked)"), 0);
+	assert_eq!(depth("second (locked)"), 1);
+	// locked after `first` was released, while `second` was still held
+	assert_eq!(depth("third (locked)"), 2);
+}
+
+// like the guards of std, so a guard can be shared with scoped threads
+#[test]
+#[cfg(feature = "enable_profiling")]
+fn guards_are_sync_test() {
+	fn assert_sync<T: Sync>(_: &T) {}
+	let mutex = Mutex::new("sync guard", 0);
+	let rw_lock = RwLock::new("sync rw guard", 0);
+	let guard = mutex.lock().unwrap();
+	assert_sync(&guard);
+	std::thread::scope(|scope| {
+		scope.spawn(|| assert_eq!(*guard, 0));
+	});
+	drop(guard);
+	assert_sync(&rw_lock.read().unwrap());
+	assert_sync(&rw_lock.write().unwrap());
+}
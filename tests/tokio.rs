@@ -0,0 +1,56 @@
+#[cfg(all(feature = "enable_profiling", feature = "tokio"))]
+use profiler::{GLOBAL_PROFILER, ScopeKind};
+#[cfg(all(feature = "enable_profiling", feature = "tokio"))]
+use std::time::Duration;
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "tokio"))]
+fn runtime_hooks_test() {
+	let runtime = profiler::tokio::instrument_runtime(&mut tokio::runtime::Builder::new_multi_thread())
+		.worker_threads(2)
+		.enable_time()
+		.build()
+		.unwrap();
+	runtime.block_on(async {
+		profiler::tokio::spawn("sleeper", async {
+			tokio::time::sleep(Duration::from_millis(20)).await;
+			tokio::time::sleep(Duration::from_millis(20)).await;
+		}).await.unwrap();
+		tokio::task::spawn_blocking(|| {
+			profiler::scope!("blocking work");
+		}).await.unwrap();
+	});
+	drop(runtime);
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let workers: Vec<_> = global_profiler.thread_profilers.values()
+		.filter(|thread_profiler| thread_profiler.name.starts_with("tokio-worker-"))
+		.collect();
+	assert_eq!(workers.len(), 2);
+	for worker in workers.iter() {
+		assert!(worker.frames.iter().all(|frame| frame.kind == profiler::tokio::WORKER_FRAME_KIND));
+	}
+	// parking ends no frame, so a worker never submits one without scopes, not even before its first park
+	assert!(workers.iter()
+		.flat_map(|worker| worker.frames.iter())
+		.all(|frame| !frame.scope_results.is_empty()));
+	let blocking = global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name.starts_with("tokio-blocking-"))
+		.unwrap();
+	assert!(blocking.frames.iter()
+		.flat_map(|frame| frame.scope_results.iter())
+		.any(|scope_result| scope_result.name.ends_with("blocking work")));
+	let park_scopes: Vec<_> = workers.iter()
+		.flat_map(|worker| worker.frames.iter())
+		.flat_map(|frame| frame.scope_results.iter())
+		.filter(|scope_result| scope_result.name == "tokio::park")
+		.collect();
+	assert!(!park_scopes.is_empty());
+	assert!(park_scopes.iter().all(|scope_result| scope_result.kind == ScopeKind::Wait));
+
+	let task_spans = global_profiler.task_spans();
+	let sleeper = task_spans.iter().find(|task_span| task_span.name == "sleeper").unwrap();
+	assert!(sleeper.completed);
+	assert_eq!(sleeper.polls, 3);
+	assert!(sleeper.duration >= Duration::from_millis(40));
+}
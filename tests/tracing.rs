@@ -0,0 +1,94 @@
+#[cfg(all(feature = "enable_profiling", feature = "tracing"))]
+use profiler::{EventKind, GLOBAL_PROFILER, Level, submit_frame};
+#[cfg(all(feature = "enable_profiling", feature = "tracing"))]
+use profiler::tracing::ProfilerLayer;
+#[cfg(all(feature = "enable_profiling", feature = "tracing"))]
+use tracing_subscriber::layer::SubscriberExt;
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "tracing"))]
+fn spans_and_events_test() {
+	let subscriber = tracing_subscriber::registry().with(ProfilerLayer::new());
+	tracing::subscriber::with_default(subscriber, || {
+		let outer = tracing::info_span!("load_level", level = 3, name = "forest");
+		let _outer = outer.enter();
+		{
+			let inner = tracing::debug_span!("parse", bytes = tracing::field::Empty);
+			let _inner = inner.enter();
+			inner.record("bytes", 1024);
+			tracing::warn!(retries = 2, "slow disk");
+		}
+	});
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "spans_and_events_test")
+		.unwrap()
+		.frames[0];
+	assert_eq!(frame.scope_results.len(), 2);
+	let parse = &frame.scope_results[0];
+	assert_eq!(parse.name, "tracing::parse");
+	assert_eq!(parse.depth, 1);
+	assert_eq!(parse.metadata, vec![("bytes".to_string(), "1024".to_string())]);
+	let load_level = &frame.scope_results[1];
+	assert_eq!(load_level.name, "tracing::load_level");
+	assert_eq!(load_level.depth, 0);
+	assert_eq!(load_level.metadata, vec![("level".to_string(), "3".to_string()), ("name".to_string(), "forest".to_string())]);
+
+	assert_eq!(frame.events.len(), 1);
+	let event = &frame.events[0];
+	assert_eq!(event.name, "slow disk");
+	assert_eq!(event.kind, EventKind::Message {
+		level: Level::Warn,
+		target: "tracing".to_string(),
+		fields: vec![("retries".to_string(), "2".to_string())],
+	});
+	assert!(parse.start <= event.time && event.time <= parse.start + parse.duration);
+}
+
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "tracing"))]
+fn out_of_order_exit_test() {
+	let subscriber = tracing_subscriber::registry().with(ProfilerLayer::new());
+	tracing::subscriber::with_default(subscriber, || {
+		let outer = tracing::info_span!("outer", id = 1);
+		let inner = tracing::info_span!("inner", id = 2);
+		let outer_guard = outer.enter();
+		let inner_guard = inner.enter();
+		drop(outer_guard);
+		drop(inner_guard);
+	});
+	submit_frame!();
+
+	let global_profiler = GLOBAL_PROFILER.lock().unwrap();
+	let frame = &global_profiler.thread_profilers.values()
+		.find(|thread_profiler| thread_profiler.name == "out_of_order_exit_test")
+		.unwrap()
+		.frames[0];
+	assert_eq!(frame.scope_results.len(), 2);
+	let outer = &frame.scope_results[0];
+	assert_eq!(outer.name, "tracing::outer");
+	assert_eq!(outer.depth, 0);
+	assert_eq!(outer.metadata, vec![("id".to_string(), "1".to_string())]);
+	let inner = &frame.scope_results[1];
+	assert_eq!(inner.name, "tracing::inner");
+	assert_eq!(inner.depth, 1);
+	assert_eq!(inner.metadata, vec![("id".to_string(), "2".to_string())]);
+}
+
+// spans still entered when a thread exits end without a profiler to record them, e.g. after `PROFILER` was destroyed
+#[test]
+#[cfg(all(feature = "enable_profiling", feature = "tracing"))]
+fn entered_span_at_thread_exit_test() {
+	std::thread::spawn(|| {
+		let subscriber = tracing_subscriber::registry().with(ProfilerLayer::new());
+		tracing::subscriber::with_default(subscriber, || {
+			// the spans of this thread are looked up before its profiler exists, so the profiler is destroyed first
+			let span = tracing::info_span!("never entered");
+			tracing::dispatcher::get_default(|dispatch| dispatch.exit(&span.id().unwrap()));
+			let span = tracing::info_span!("still entered");
+			std::mem::forget(span.enter());
+		});
+	}).join().unwrap();
+}